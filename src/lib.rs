@@ -1,19 +1,33 @@
 pub mod types;
 
+// So #[derive(Vertex)] can name the trait as ::wgpu_ex::... in here too
+extern crate self as wgpu_ex;
+
 use winit::{
     event::*,
     event_loop::EventLoop,
     keyboard::{KeyCode, PhysicalKey},
-    window::WindowBuilder,
+    window::{Fullscreen, WindowBuilder},
 };
 
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
-use types::state::State;
+#[cfg(not(target_arch = "wasm32"))]
+use types::shader_library::ShaderLibrary;
+use types::{
+    cli::Args,
+    state::State,
+    timing::{FixedTimestep, FrameTimer},
+};
 
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen(start))]
 pub async fn run() {
+    run_with(Args::default()).await
+}
+
+// What main.rs runs, with the options from the command line
+pub async fn run_with(args: Args) {
     cfg_if::cfg_if! {
         if #[cfg(target_arch = "wasm32")] {
             std::panic::set_hook(Box::new(console_error_panic_hook::hook));
@@ -24,7 +38,14 @@ pub async fn run() {
     }
 
     let event_loop = EventLoop::new().unwrap();
-    let window = WindowBuilder::new().build(&event_loop).unwrap();
+    let mut window_builder = WindowBuilder::new();
+    if let Some(size) = args.size {
+        window_builder = window_builder.with_inner_size(size);
+    }
+    if args.fullscreen {
+        window_builder = window_builder.with_fullscreen(Some(Fullscreen::Borderless(None)));
+    }
+    let window = window_builder.build(&event_loop).unwrap();
 
     #[cfg(target_arch = "wasm32")]
     {
@@ -46,63 +67,133 @@ pub async fn run() {
         let _ = window.request_inner_size(PhysicalSize::new(630, 560));
     }
 
-    let mut state = State::new(&window).await;
+    let mut state = State::new(&window, &args.render).await;
+    // --hot-reload (or setting WGPU_EX_HOT_RELOAD) reads the shaders from
+    // src/types/resources while running, so saving one rebuilds its pipelines
+    // straight away
+    #[cfg(not(target_arch = "wasm32"))]
+    if args.hot_reload || std::env::var_os("WGPU_EX_HOT_RELOAD").is_some() {
+        state.set_shader_library(ShaderLibrary::from_dir(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/types/resources"
+        )));
+    }
+    if let Some(index) = args.example {
+        state.set_example(index);
+    }
     let mut surface_configured = false;
+    let mut frames_drawn = 0;
+    let mut timer = FrameTimer::new();
+    // The scene is updated in steps of the same size however fast it's drawn, so
+    // the camera moves the same way at any frame rate
+    let mut fixed = FixedTimestep::from_hz(120.0);
 
-    event_loop.run(move |event, control_flow| {
-        match event {
-            Event::WindowEvent {
-                ref event,
-                window_id,
-            } if window_id == state.window().id() => if !state.input(event) {
-                match event {
-                    WindowEvent::CloseRequested
-                    | WindowEvent::KeyboardInput {
-                        event:
-                            KeyEvent {
-                                state: ElementState::Pressed,
-                                physical_key: PhysicalKey::Code(KeyCode::Escape),
-                                ..
-                            },
-                        ..
-                    } => control_flow.exit(),
-                    WindowEvent::Resized(physical_size) => {
-                        // log::info!("physical_size: {physical_size:?}");
-                        surface_configured = true;
-                        state.resize(*physical_size);
+    event_loop
+        .run(move |event, control_flow| {
+            match event {
+                Event::WindowEvent {
+                    ref event,
+                    window_id,
+                } if Some(window_id) == state.window().map(|w| w.id()) && !state.input(event) => {
+                    // The number keys switch between the tutorial's stages
+                    if let Some(index) = types::examples::hotkey(event) {
+                        state.set_example(index);
+                        return;
                     }
-                    WindowEvent::RedrawRequested => {
-                        // This tells winit that we want another frame after this one
-                        state.window().request_redraw();
-            
-                        if !surface_configured {
-                            return;
+
+                    match event {
+                        WindowEvent::CloseRequested
+                        | WindowEvent::KeyboardInput {
+                            event:
+                                KeyEvent {
+                                    state: ElementState::Pressed,
+                                    physical_key: PhysicalKey::Code(KeyCode::Escape),
+                                    ..
+                                },
+                            ..
+                        } => control_flow.exit(),
+                        // F12 draws one extra frame, presented like any other, and saves it as a PNG
+                        // in the working directory. Nothing is updated in between, so it's the same
+                        // picture as the frame before it.
+                        #[cfg(not(target_arch = "wasm32"))]
+                        WindowEvent::KeyboardInput {
+                            event:
+                                KeyEvent {
+                                    state: ElementState::Pressed,
+                                    physical_key: PhysicalKey::Code(KeyCode::F12),
+                                    repeat: false,
+                                    ..
+                                },
+                            ..
+                        } if surface_configured => {
+                            let timestamp = std::time::SystemTime::now()
+                                .duration_since(std::time::UNIX_EPOCH)
+                                .map(|d| d.as_millis())
+                                .unwrap_or_default();
+                            let path = format!("screenshot-{timestamp}.png");
+                            match state.save_screenshot(&path) {
+                                Ok(()) => log::info!("Saved screenshot to {path}"),
+                                Err(e) => log::error!("Failed to save screenshot: {e:#}"),
+                            }
+                        }
+                        WindowEvent::Resized(physical_size) => {
+                            // log::info!("physical_size: {physical_size:?}");
+                            surface_configured = true;
+                            state.resize(*physical_size);
                         }
-            
-                        state.update();
-                        match state.render() {
-                            Ok(_) => {}
-                            // Reconfigure the surface if it's lost or outdated
-                            Err(
-                                wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated,
-                            ) => state.resize(state.size),
-                            // The system is out of memory, we should probably quit
-                            Err(wgpu::SurfaceError::OutOfMemory | wgpu::SurfaceError::Other) => {
-                                log::error!("OutOfMemory");
-                                control_flow.exit();
+                        WindowEvent::RedrawRequested => {
+                            // This tells winit that we want another frame after this one
+                            if let Some(window) = state.window() {
+                                window.request_redraw();
                             }
-            
-                            // This happens when the a frame takes too long to present
-                            Err(wgpu::SurfaceError::Timeout) => {
-                                log::warn!("Surface timeout")
+
+                            // Ticked even when nothing is drawn, so the first real
+                            // frame doesn't think it took the whole wait
+                            let dt = timer.tick();
+
+                            if !surface_configured {
+                                return;
+                            }
+
+                            for _ in 0..fixed.advance(dt) {
+                                state.update(fixed.step());
+                            }
+                            match state.render() {
+                                Ok(_) => {
+                                    // --frames stops here, for scripted runs
+                                    frames_drawn += 1;
+                                    if args.frames == Some(frames_drawn) {
+                                        control_flow.exit();
+                                    }
+                                }
+                                // Reconfigure the surface if it's lost or outdated
+                                Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                                    state.resize(state.size)
+                                }
+                                // The system is out of memory, we should probably quit
+                                Err(
+                                    wgpu::SurfaceError::OutOfMemory | wgpu::SurfaceError::Other,
+                                ) => {
+                                    log::error!("OutOfMemory");
+                                    control_flow.exit();
+                                }
+
+                                // This happens when the a frame takes too long to present
+                                Err(wgpu::SurfaceError::Timeout) => {
+                                    log::warn!("Surface timeout")
+                                }
                             }
                         }
-                    },
-                    _ => {}
+                        _ => {}
+                    }
                 }
-            },
-            _ => {}
-        }
-    })
+                // Raw mouse motion keeps coming while the cursor is grabbed, which
+                // the fly camera needs
+                Event::DeviceEvent { ref event, .. } => {
+                    state.device_input(event);
+                }
+                _ => {}
+            }
+        })
         .unwrap();
-}
\ No newline at end of file
+}
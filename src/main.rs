@@ -1,5 +1,20 @@
-use wgpu_ex::run;
+use wgpu_ex::{
+    run_with,
+    types::cli::{Args, USAGE},
+};
 
 fn main() {
-    pollster::block_on(run());
-}
\ No newline at end of file
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{USAGE}");
+            return;
+        }
+        Err(e) => {
+            eprintln!("{e:#}\n\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
+    pollster::block_on(run_with(args));
+}
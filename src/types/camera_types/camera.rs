@@ -1,25 +1,163 @@
-#[rustfmt::skip]
-pub const OPENGL_TO_WGPU_MATRIX: cgmath::Matrix4<f32> = cgmath::Matrix4::new(
-    1.0, 0.0, 0.0, 0.0,
-    0.0, 1.0, 0.0, 0.0,
-    0.0, 0.0, 0.5, 0.5,
-    0.0, 0.0, 0.0, 1.0,
+// Maps OpenGL's -1.0 to 1.0 depth range onto wgpu's 0.0 to 1.0. Matrix4::new takes
+// columns, so this is written with from_cols to keep the 0.5 offset in the last column.
+pub const OPENGL_TO_WGPU_MATRIX: cgmath::Matrix4<f32> = cgmath::Matrix4::from_cols(
+    cgmath::Vector4::new(1.0, 0.0, 0.0, 0.0),
+    cgmath::Vector4::new(0.0, 1.0, 0.0, 0.0),
+    cgmath::Vector4::new(0.0, 0.0, 0.5, 0.0),
+    cgmath::Vector4::new(0.0, 0.0, 0.5, 1.0),
 );
 
+// How much of an orthographic view is visible
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum OrthographicSize {
+    // Half the height of the view, the width follows the aspect ratio
+    Extent(f32),
+    // The smallest area that stays visible. It's widened (or made taller) around
+    // its center to match the aspect ratio, so nothing gets stretched.
+    Bounds {
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+    },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Projection {
+    // Things further away look smaller. `zfar` can be f32::INFINITY for a far plane
+    // that never clips anything.
+    Perspective {
+        fovy: cgmath::Deg<f32>,
+        znear: f32,
+        zfar: f32,
+    },
+    // Things stay the same size however far away they are, for 2D and CAD style views.
+    // `zfar` has to be finite, which validate checks.
+    Orthographic {
+        size: OrthographicSize,
+        znear: f32,
+        zfar: f32,
+    },
+}
+
+impl Projection {
+    // Whether matrix can make sense of this projection. State::set_projection
+    // turns down anything that fails.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Projection::Orthographic { zfar, .. } = *self {
+            // Depth is spread evenly between the planes, so there's nothing to
+            // spread it over with no far plane. It would all come out NaN.
+            anyhow::ensure!(
+                zfar.is_finite(),
+                "an orthographic projection needs a finite zfar, not {zfar}"
+            );
+        }
+        Ok(())
+    }
+
+    // Goes straight to wgpu's clip space, where depth is 0.0 (near) to 1.0 (far), or
+    // 1.0 to 0.0 with reverse-Z. Reverse-Z spreads the depth buffer's precision
+    // much more evenly, but needs a Greater depth compare and clearing to 0.0.
+    // A projection that doesn't pass validate comes out as NaNs.
+    pub fn matrix(&self, aspect: f32, reverse_z: bool) -> cgmath::Matrix4<f32> {
+        match *self {
+            Projection::Perspective {
+                fovy,
+                znear: n,
+                zfar: f,
+            } => {
+                let focal = 1.0 / (cgmath::Rad::from(fovy).0 / 2.0).tan();
+                // How depth is worked out from the view space z (m22) and w (m32)
+                let (m22, m32) = match (reverse_z, f.is_infinite()) {
+                    (false, false) => (f / (n - f), n * f / (n - f)),
+                    (false, true) => (-1.0, -n),
+                    (true, false) => (n / (f - n), n * f / (f - n)),
+                    (true, true) => (0.0, n),
+                };
+
+                // Each row here is a column of the matrix
+                #[rustfmt::skip]
+                let matrix = cgmath::Matrix4::new(
+                    focal / aspect, 0.0, 0.0, 0.0,
+                    0.0, focal, 0.0, 0.0,
+                    0.0, 0.0, m22, -1.0,
+                    0.0, 0.0, m32, 0.0,
+                );
+                matrix
+            }
+            Projection::Orthographic {
+                size,
+                znear: n,
+                zfar: f,
+            } => {
+                let (l, r, b, t) = size.bounds(aspect);
+                let (m22, m32) = if reverse_z {
+                    (1.0 / (f - n), f / (f - n))
+                } else {
+                    (1.0 / (n - f), n / (n - f))
+                };
+
+                #[rustfmt::skip]
+                let matrix = cgmath::Matrix4::new(
+                    2.0 / (r - l), 0.0, 0.0, 0.0,
+                    0.0, 2.0 / (t - b), 0.0, 0.0,
+                    0.0, 0.0, m22, 0.0,
+                    -(r + l) / (r - l), -(t + b) / (t - b), m32, 1.0,
+                );
+                matrix
+            }
+        }
+    }
+}
+
+impl OrthographicSize {
+    // (left, right, bottom, top) for a view with the given aspect ratio
+    pub fn bounds(&self, aspect: f32) -> (f32, f32, f32, f32) {
+        match *self {
+            OrthographicSize::Extent(half_height) => {
+                let half_width = half_height * aspect;
+                (-half_width, half_width, -half_height, half_height)
+            }
+            OrthographicSize::Bounds {
+                left,
+                right,
+                bottom,
+                top,
+            } => {
+                let (center_x, center_y) = ((left + right) / 2.0, (bottom + top) / 2.0);
+                let (mut half_width, mut half_height) =
+                    ((right - left) / 2.0, (top - bottom) / 2.0);
+                if half_width / half_height < aspect {
+                    half_width = half_height * aspect;
+                } else {
+                    half_height = half_width / aspect;
+                }
+                (
+                    center_x - half_width,
+                    center_x + half_width,
+                    center_y - half_height,
+                    center_y + half_height,
+                )
+            }
+        }
+    }
+}
+
 pub struct Camera {
     pub eye: cgmath::Point3<f32>,
     pub target: cgmath::Point3<f32>,
     pub up: cgmath::Vector3<f32>,
+    // Width / height of what the camera renders to, kept up to date by State::resize
     pub aspect: f32,
-    pub fovy: f32,
-    pub znear: f32,
-    pub zfar: f32,
+    pub projection: Projection,
+    pub reverse_z: bool,
 }
 
 impl Camera {
+    // Already in wgpu's clip space, unlike cgmath's projections which are made for OpenGL.
     pub fn build_view_projection_matrix(&self) -> cgmath::Matrix4<f32> {
         let view = cgmath::Matrix4::look_at_rh(self.eye, self.target, self.up);
-        let proj = cgmath::perspective(cgmath::Deg(self.fovy), self.aspect, self.znear, self.zfar);
+        let proj = self.projection.matrix(self.aspect, self.reverse_z);
         proj * view
     }
-}
\ No newline at end of file
+}
@@ -1,9 +1,21 @@
-use super::camera::{Camera, OPENGL_TO_WGPU_MATRIX};
+use super::camera::Camera;
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct CameraUniform {
     view_proj: [[f32; 4]; 4],
+    // Only xyz is used, w pads it out to the 16 bytes uniforms need
+    view_position: [f32; 4],
+    // Takes clip space back to world space, for shaders (like the skybox) that
+    // work out what the camera sees through each pixel. Shaders that don't need
+    // it can leave it off the end of their CameraUniform.
+    inv_view_proj: [[f32; 4]; 4],
+}
+
+impl Default for CameraUniform {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl CameraUniform {
@@ -11,10 +23,20 @@ impl CameraUniform {
         use cgmath::SquareMatrix;
         Self {
             view_proj: cgmath::Matrix4::identity().into(),
+            view_position: [0.0; 4],
+            inv_view_proj: cgmath::Matrix4::identity().into(),
         }
     }
 
     pub fn update_view_proj(&mut self, camera: &Camera) {
-        self.view_proj = (OPENGL_TO_WGPU_MATRIX * camera.build_view_projection_matrix()).into();
+        use cgmath::SquareMatrix;
+        let view_proj = camera.build_view_projection_matrix();
+        self.view_position = camera.eye.to_homogeneous().into();
+        self.view_proj = view_proj.into();
+        // Only a degenerate camera (eye on its target, zero sized view) has no inverse
+        self.inv_view_proj = view_proj
+            .invert()
+            .unwrap_or_else(cgmath::Matrix4::identity)
+            .into();
     }
 }
@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use cgmath::{InnerSpace, Rad};
+use winit::{
+    event::{ElementState, KeyEvent, WindowEvent},
+    keyboard::{KeyCode, PhysicalKey},
+};
+
+use super::camera::Camera;
+
+// Stops the camera from looking straight up or down, where yaw stops meaning anything
+const SAFE_PITCH: Rad<f32> = Rad(std::f32::consts::FRAC_PI_2 - 0.01);
+
+// A camera that's pointed by turning it (yaw) and tilting it (pitch), instead
+// of by giving it something to look at. y is up.
+#[derive(Copy, Clone, Debug)]
+pub struct FpsCamera {
+    pub position: cgmath::Point3<f32>,
+    // 0 looks along +x, turning towards +z
+    pub yaw: Rad<f32>,
+    // 0 is level, positive looks up
+    pub pitch: Rad<f32>,
+}
+
+impl FpsCamera {
+    pub fn new(
+        position: cgmath::Point3<f32>,
+        yaw: impl Into<Rad<f32>>,
+        pitch: impl Into<Rad<f32>>,
+    ) -> Self {
+        Self {
+            position,
+            yaw: yaw.into(),
+            pitch: pitch.into(),
+        }
+    }
+
+    // Starts out at `eye` facing `target`, e.g. to take over from an orbiting Camera.
+    pub fn looking_at(eye: cgmath::Point3<f32>, target: cgmath::Point3<f32>) -> Self {
+        let direction = (target - eye).normalize();
+        Self {
+            position: eye,
+            yaw: Rad(direction.z.atan2(direction.x)),
+            pitch: Rad(direction.y.clamp(-1.0, 1.0).asin()),
+        }
+    }
+
+    pub fn forward(&self) -> cgmath::Vector3<f32> {
+        let (sin_yaw, cos_yaw) = self.yaw.0.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.0.sin_cos();
+        cgmath::Vector3::new(cos_pitch * cos_yaw, sin_pitch, cos_pitch * sin_yaw)
+    }
+
+    pub fn view_matrix(&self) -> cgmath::Matrix4<f32> {
+        cgmath::Matrix4::look_to_rh(self.position, self.forward(), cgmath::Vector3::unit_y())
+    }
+
+    // Points `camera` the same way, the projection settings are left as they are.
+    pub fn apply_to(&self, camera: &mut Camera) {
+        camera.eye = self.position;
+        camera.target = self.position + self.forward();
+        camera.up = cgmath::Vector3::unit_y();
+    }
+}
+
+pub struct FpsController {
+    // Distance moved per second
+    pub speed: f32,
+    // Radians turned per unit of mouse motion
+    pub sensitivity: f32,
+    is_forward_pressed: bool,
+    is_backward_pressed: bool,
+    is_left_pressed: bool,
+    is_right_pressed: bool,
+    is_up_pressed: bool,
+    is_down_pressed: bool,
+    // Mouse motion collected since the last update_camera
+    rotate_delta: (f32, f32),
+}
+
+impl FpsController {
+    pub fn new(speed: f32, sensitivity: f32) -> Self {
+        Self {
+            speed,
+            sensitivity,
+            is_forward_pressed: false,
+            is_backward_pressed: false,
+            is_left_pressed: false,
+            is_right_pressed: false,
+            is_up_pressed: false,
+            is_down_pressed: false,
+            rotate_delta: (0.0, 0.0),
+        }
+    }
+
+    pub fn process_events(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        state,
+                        physical_key: PhysicalKey::Code(keycode),
+                        ..
+                    },
+                ..
+            } => self.process_keyboard(*keycode, *state),
+            _ => false,
+        }
+    }
+
+    pub fn process_keyboard(&mut self, keycode: KeyCode, state: ElementState) -> bool {
+        let is_pressed = state == ElementState::Pressed;
+        match keycode {
+            KeyCode::KeyW | KeyCode::ArrowUp => self.is_forward_pressed = is_pressed,
+            KeyCode::KeyS | KeyCode::ArrowDown => self.is_backward_pressed = is_pressed,
+            KeyCode::KeyA | KeyCode::ArrowLeft => self.is_left_pressed = is_pressed,
+            KeyCode::KeyD | KeyCode::ArrowRight => self.is_right_pressed = is_pressed,
+            KeyCode::Space => self.is_up_pressed = is_pressed,
+            KeyCode::ShiftLeft | KeyCode::ShiftRight => self.is_down_pressed = is_pressed,
+            _ => return false,
+        }
+        true
+    }
+
+    // Raw mouse motion, from DeviceEvent::MouseMotion. Unlike cursor positions
+    // this keeps coming while the pointer is locked.
+    pub fn process_mouse(&mut self, dx: f64, dy: f64) {
+        self.rotate_delta.0 += dx as f32;
+        self.rotate_delta.1 += dy as f32;
+    }
+
+    // Lets go of every key, e.g. when focus is lost and the key ups won't arrive
+    pub fn reset(&mut self) {
+        *self = Self::new(self.speed, self.sensitivity);
+    }
+
+    // Turning comes from however far the mouse moved, so only movement depends on `dt`.
+    pub fn update_camera(&mut self, camera: &mut FpsCamera, dt: Duration) {
+        let (dx, dy) = std::mem::take(&mut self.rotate_delta);
+        camera.yaw += Rad(dx * self.sensitivity);
+        // Moving the mouse up looks up, but screen y points down
+        camera.pitch =
+            Rad((camera.pitch.0 - dy * self.sensitivity).clamp(-SAFE_PITCH.0, SAFE_PITCH.0));
+
+        // Forward and backward follow the view, so looking up and pressing W flies up
+        let forward = camera.forward();
+        let right = forward.cross(cgmath::Vector3::unit_y()).normalize();
+
+        let axis = |positive: bool, negative: bool| positive as i32 as f32 - negative as i32 as f32;
+        let movement = forward * axis(self.is_forward_pressed, self.is_backward_pressed)
+            + right * axis(self.is_right_pressed, self.is_left_pressed)
+            + cgmath::Vector3::unit_y() * axis(self.is_up_pressed, self.is_down_pressed);
+
+        // Normalized so moving diagonally isn't faster
+        if movement.magnitude2() > 0.0 {
+            camera.position += movement.normalize() * self.speed * dt.as_secs_f32();
+        }
+    }
+}
@@ -1,3 +1,5 @@
 pub mod camera;
+pub mod camera_controller;
 pub mod camera_uniform;
-pub mod camera_controller;
\ No newline at end of file
+pub mod fps_camera;
+pub mod orbit_controller;
@@ -0,0 +1,165 @@
+use cgmath::{InnerSpace, Rotation, Rotation3};
+use winit::{
+    dpi::PhysicalPosition,
+    event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent},
+};
+
+use super::camera::Camera;
+
+// Moves the camera around its target with the mouse:
+// left drag orbits, middle drag pans and the scroll wheel zooms.
+pub struct OrbitController {
+    // Radians per pixel dragged
+    pub rotate_speed: f32,
+    // Fraction of the distance to the target moved per scroll line
+    pub zoom_speed: f32,
+    // Fraction of the distance to the target moved per pixel dragged
+    pub pan_speed: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    // How far above or below the target the camera can go. Kept short of 90°
+    // so the camera never looks straight along `up` and flips over.
+    pub max_pitch: cgmath::Rad<f32>,
+    is_rotating: bool,
+    is_panning: bool,
+    last_cursor: Option<PhysicalPosition<f64>>,
+    // Input collected since the last update_camera
+    rotate_delta: (f32, f32),
+    pan_delta: (f32, f32),
+    zoom_delta: f32,
+}
+
+impl OrbitController {
+    pub fn new(min_distance: f32, max_distance: f32) -> Self {
+        Self {
+            rotate_speed: 0.01,
+            zoom_speed: 0.1,
+            pan_speed: 0.002,
+            min_distance,
+            max_distance,
+            max_pitch: cgmath::Deg(89.0).into(),
+            is_rotating: false,
+            is_panning: false,
+            last_cursor: None,
+            rotate_delta: (0.0, 0.0),
+            pan_delta: (0.0, 0.0),
+            zoom_delta: 0.0,
+        }
+    }
+
+    pub fn process_events(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::MouseInput { state, button, .. } => {
+                let is_pressed = *state == ElementState::Pressed;
+                match button {
+                    MouseButton::Left => self.is_rotating = is_pressed,
+                    MouseButton::Middle => self.is_panning = is_pressed,
+                    _ => return false,
+                }
+                true
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let last = self.last_cursor.replace(*position);
+                match last {
+                    Some(last) if self.is_rotating || self.is_panning => {
+                        let dx = (position.x - last.x) as f32;
+                        let dy = (position.y - last.y) as f32;
+                        if self.is_rotating {
+                            self.rotate(dx, dy);
+                        } else {
+                            self.pan(dx, dy);
+                        }
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            WindowEvent::CursorLeft { .. } => {
+                // Otherwise the next CursorMoved would jump from wherever the cursor left
+                self.last_cursor = None;
+                false
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                self.zoom(match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    // Roughly how many pixels one line of a scroll wheel is
+                    MouseScrollDelta::PixelDelta(position) => position.y as f32 / 20.0,
+                });
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Queues an orbit by the given number of pixels dragged.
+    pub fn rotate(&mut self, dx: f32, dy: f32) {
+        self.rotate_delta.0 += dx;
+        self.rotate_delta.1 += dy;
+    }
+
+    // Queues a pan by the given number of pixels dragged.
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        self.pan_delta.0 += dx;
+        self.pan_delta.1 += dy;
+    }
+
+    // Queues a zoom by the given number of scroll lines, positive moves closer.
+    pub fn zoom(&mut self, lines: f32) {
+        self.zoom_delta += lines;
+    }
+
+    pub fn update_camera(&mut self, camera: &mut Camera) {
+        let (rotate_x, rotate_y) = std::mem::take(&mut self.rotate_delta);
+        let (pan_x, pan_y) = std::mem::take(&mut self.pan_delta);
+        let zoom = std::mem::take(&mut self.zoom_delta);
+
+        // Leaves the camera alone (e.g. for the keyboard controller) until there's mouse input
+        if (rotate_x, rotate_y, pan_x, pan_y, zoom) == (0.0, 0.0, 0.0, 0.0, 0.0) {
+            return;
+        }
+
+        let up = camera.up.normalize();
+        let offset = camera.eye - camera.target;
+        let distance = offset.magnitude();
+        if distance <= f32::EPSILON {
+            return;
+        }
+
+        // Split the offset into how far it's tilted towards `up` and which way it
+        // points around it, then rotate each of those separately.
+        let pitch = (offset.dot(up) / distance).clamp(-1.0, 1.0).asin();
+        let horizontal = offset - up * offset.dot(up);
+        let horizontal = if horizontal.magnitude2() > f32::EPSILON {
+            horizontal.normalize()
+        } else {
+            // Looking straight down `up`, any direction around it will do
+            let any = if up.x.abs() < 0.9 {
+                cgmath::Vector3::unit_x()
+            } else {
+                cgmath::Vector3::unit_z()
+            };
+            up.cross(any).normalize()
+        };
+
+        let yaw =
+            cgmath::Quaternion::from_axis_angle(up, cgmath::Rad(-rotate_x * self.rotate_speed));
+        let horizontal = yaw.rotate_vector(horizontal);
+        let pitch =
+            (pitch + rotate_y * self.rotate_speed).clamp(-self.max_pitch.0, self.max_pitch.0);
+
+        // Zooming by a fraction of the distance feels the same near and far away
+        let distance = (distance * (1.0 - self.zoom_speed).powf(zoom))
+            .clamp(self.min_distance, self.max_distance);
+
+        let offset = (horizontal * pitch.cos() + up * pitch.sin()) * distance;
+
+        // Panning drags the target and the eye across the screen together
+        let forward = -offset.normalize();
+        let right = forward.cross(up).normalize();
+        let screen_up = right.cross(forward);
+        let pan = (-right * pan_x + screen_up * pan_y) * self.pan_speed * distance;
+
+        camera.target += pan;
+        camera.eye = camera.target + offset;
+    }
+}
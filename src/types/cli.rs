@@ -0,0 +1,143 @@
+use anyhow::{Context, Result, bail};
+
+use super::{examples, state::RenderOptions};
+
+pub const USAGE: &str = "\
+Usage: wgpu_ex [options]
+
+Options:
+  --example <name|number>  start on one of the tutorial's stages, or \"scene\"
+  --size <width>x<height>  the window's size in pixels, like 1280x720
+  --fullscreen             borderless fullscreen on the current monitor
+  --backend <backend>      vulkan, gl or any
+  --power <preference>     low or high, for machines with more than one GPU
+  --present-mode <mode>    fifo, fifo-relaxed, mailbox, immediate, auto-vsync or auto-no-vsync
+  --msaa <samples>         MSAA for the scene, 1 (off), 2, 4, 8 or 16
+  --frames <count>         exit after drawing this many frames
+  --hot-reload             rebuild shaders from src/types/resources when they're saved
+  -h, --help               print this and exit";
+
+// Everything the desktop binary can be told on its command line. The defaults
+// are what it does without any arguments.
+#[derive(Default)]
+pub struct Args {
+    pub render: RenderOptions,
+    // An index into examples::EXAMPLES, None for the full scene
+    pub example: Option<usize>,
+    pub size: Option<winit::dpi::PhysicalSize<u32>>,
+    pub fullscreen: bool,
+    pub frames: Option<u64>,
+    pub hot_reload: bool,
+}
+
+impl Args {
+    // Parses the arguments after the program's name. None means --help was asked
+    // for, and USAGE should be printed instead of running.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Self>> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            // Values can come after an = or as the next argument
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => {
+                    (flag.to_string(), Some(value.to_string()))
+                }
+                _ => (arg, None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .with_context(|| format!("{flag} needs a value"))
+            };
+
+            match flag.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--example" => {
+                    let name = value()?;
+                    let index = examples::find(&name).with_context(|| {
+                        let names = examples::EXAMPLES
+                            .iter()
+                            .map(|example| example.name)
+                            .collect::<Vec<_>>();
+                        format!(
+                            "there's no example called {name}, try one of {}",
+                            names.join(", ")
+                        )
+                    })?;
+                    parsed.example = Some(index);
+                }
+                "--size" => parsed.size = Some(parse_size(&value()?)?),
+                "--fullscreen" => parsed.fullscreen = true,
+                "--backend" => {
+                    parsed.render.backends = match value()?.as_str() {
+                        "vulkan" => wgpu::Backends::VULKAN,
+                        "gl" => wgpu::Backends::GL,
+                        "any" => wgpu::Backends::all(),
+                        other => bail!("unknown backend {other}, try vulkan, gl or any"),
+                    }
+                }
+                "--power" => {
+                    parsed.render.power_preference = match value()?.as_str() {
+                        "low" => wgpu::PowerPreference::LowPower,
+                        "high" => wgpu::PowerPreference::HighPerformance,
+                        other => bail!("unknown power preference {other}, try low or high"),
+                    }
+                }
+                "--present-mode" => {
+                    parsed.render.present_mode = Some(match value()?.as_str() {
+                        "fifo" => wgpu::PresentMode::Fifo,
+                        "fifo-relaxed" => wgpu::PresentMode::FifoRelaxed,
+                        "mailbox" => wgpu::PresentMode::Mailbox,
+                        "immediate" => wgpu::PresentMode::Immediate,
+                        "auto-vsync" => wgpu::PresentMode::AutoVsync,
+                        "auto-no-vsync" => wgpu::PresentMode::AutoNoVsync,
+                        other => bail!(
+                            "unknown present mode {other}, try fifo, fifo-relaxed, mailbox, immediate, auto-vsync or auto-no-vsync"
+                        ),
+                    })
+                }
+                "--msaa" => {
+                    let value = value()?;
+                    let samples = value
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|samples| [1, 2, 4, 8, 16].contains(samples));
+                    parsed.render.sample_count = samples.with_context(|| {
+                        format!("--msaa takes 1, 2, 4, 8 or 16 samples, not {value}")
+                    })?;
+                }
+                "--frames" => {
+                    let value = value()?;
+                    let frames = value.parse::<u64>().ok().filter(|&frames| frames > 0);
+                    parsed.frames = Some(frames.with_context(|| {
+                        format!("--frames needs a number of frames above 0, not {value}")
+                    })?);
+                }
+                "--hot-reload" => parsed.hot_reload = true,
+                _ => bail!("unknown argument {flag}"),
+            }
+
+            // Flags that don't take a value mustn't be given one either
+            if inline_value.is_some()
+                && matches!(
+                    flag.as_str(),
+                    "--fullscreen" | "--hot-reload" | "-h" | "--help"
+                )
+            {
+                bail!("{flag} doesn't take a value");
+            }
+        }
+        Ok(Some(parsed))
+    }
+}
+
+fn parse_size(value: &str) -> Result<winit::dpi::PhysicalSize<u32>> {
+    let size = value
+        .split_once('x')
+        .and_then(|(width, height)| Some((width.parse::<u32>().ok()?, height.parse::<u32>().ok()?)))
+        .filter(|&(width, height)| width > 0 && height > 0);
+    let (width, height) = size
+        .with_context(|| format!("--size needs a width and height like 1280x720, not {value}"))?;
+    Ok(winit::dpi::PhysicalSize::new(width, height))
+}
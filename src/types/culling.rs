@@ -0,0 +1,197 @@
+use std::ops::Range;
+
+use cgmath::{EuclideanSpace, InnerSpace, Matrix, Transform};
+
+use super::vertex_types::instance::InstanceRaw;
+
+// Axis aligned bounding box
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: cgmath::Point3<f32>,
+    pub max: cgmath::Point3<f32>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BoundingSphere {
+    pub center: cgmath::Point3<f32>,
+    pub radius: f32,
+}
+
+impl Aabb {
+    // An empty set of points gets an empty box at the origin
+    pub fn from_points(points: impl IntoIterator<Item = [f32; 3]>) -> Self {
+        let mut points = points.into_iter().map(cgmath::Point3::from);
+        let Some(first) = points.next() else {
+            return Self {
+                min: cgmath::Point3::origin(),
+                max: cgmath::Point3::origin(),
+            };
+        };
+
+        points.fold(
+            Self {
+                min: first,
+                max: first,
+            },
+            |aabb, p| Self {
+                min: cgmath::Point3::new(
+                    aabb.min.x.min(p.x),
+                    aabb.min.y.min(p.y),
+                    aabb.min.z.min(p.z),
+                ),
+                max: cgmath::Point3::new(
+                    aabb.max.x.max(p.x),
+                    aabb.max.y.max(p.y),
+                    aabb.max.z.max(p.z),
+                ),
+            },
+        )
+    }
+
+    pub fn center(&self) -> cgmath::Point3<f32> {
+        self.min.midpoint(self.max)
+    }
+
+    pub fn corners(&self) -> [cgmath::Point3<f32>; 8] {
+        let (min, max) = (self.min, self.max);
+        [0, 1, 2, 3, 4, 5, 6, 7].map(|i| {
+            cgmath::Point3::new(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            )
+        })
+    }
+
+    // The box around this one after it's been moved by `transform`, which is
+    // usually a bit bigger than the transformed box itself.
+    pub fn transformed(&self, transform: &cgmath::Matrix4<f32>) -> Self {
+        Self::from_points(self.corners().map(|p| transform.transform_point(p).into()))
+    }
+}
+
+impl BoundingSphere {
+    // Centered on the middle of the points' bounding box, which is close to the
+    // smallest sphere for most meshes and much cheaper to find.
+    pub fn from_points(points: &[[f32; 3]]) -> Self {
+        let center = Aabb::from_points(points.iter().copied()).center();
+        let radius = points
+            .iter()
+            .map(|p| (cgmath::Point3::from(*p) - center).magnitude())
+            .fold(0.0, f32::max);
+        Self { center, radius }
+    }
+
+    // Non-uniform scaling turns a sphere into an ellipsoid, this is the sphere around that.
+    pub fn transformed(&self, transform: &cgmath::Matrix4<f32>) -> Self {
+        let scale = [transform.x, transform.y, transform.z]
+            .iter()
+            .map(|axis| axis.truncate().magnitude())
+            .fold(0.0, f32::max);
+        Self {
+            center: transform.transform_point(self.center),
+            radius: self.radius * scale,
+        }
+    }
+}
+
+// The six planes around what a camera can see. Each is stored as (normal, distance)
+// with the normal pointing inwards, so a point p is inside when normal·p + distance >= 0.
+#[derive(Copy, Clone, Debug)]
+pub struct Frustum {
+    pub planes: [cgmath::Vector4<f32>; 6],
+}
+
+impl Frustum {
+    // Works for any matrix going to wgpu's clip space, where x and y run from -w to w
+    // and z from 0 to w. Reverse-Z and infinite far planes come out right as well.
+    pub fn from_matrix(view_proj: &cgmath::Matrix4<f32>) -> Self {
+        let [r0, r1, r2, r3] = [0, 1, 2, 3].map(|i| view_proj.row(i));
+        let planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r2, r3 - r2].map(|plane| {
+            let length = plane.truncate().magnitude();
+            // An infinite far plane has no normal at all, and contains everything
+            if length > 0.0 { plane / length } else { plane }
+        });
+        Self { planes }
+    }
+
+    fn distance(plane: &cgmath::Vector4<f32>, point: cgmath::Point3<f32>) -> f32 {
+        plane.truncate().dot(point.to_vec()) + plane.w
+    }
+
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| Self::distance(plane, sphere.center) >= -sphere.radius)
+    }
+
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            // Only the corner furthest along the normal needs checking: if that
+            // one's outside, the whole box is
+            let corner = cgmath::Point3::new(
+                if plane.x >= 0.0 {
+                    aabb.max.x
+                } else {
+                    aabb.min.x
+                },
+                if plane.y >= 0.0 {
+                    aabb.max.y
+                } else {
+                    aabb.min.y
+                },
+                if plane.z >= 0.0 {
+                    aabb.max.z
+                } else {
+                    aabb.min.z
+                },
+            );
+            Self::distance(plane, corner) >= 0.0
+        })
+    }
+
+    // The cheap sphere test throws out most things, the box test catches long thin
+    // meshes whose spheres are much bigger than they are.
+    pub fn intersects(
+        &self,
+        aabb: &Aabb,
+        sphere: &BoundingSphere,
+        transform: &cgmath::Matrix4<f32>,
+    ) -> bool {
+        self.intersects_sphere(&sphere.transformed(transform))
+            && self.intersects_aabb(&aabb.transformed(transform))
+    }
+
+    // The instances of a mesh that can be seen, as ranges ready for draw_indexed.
+    // Neighbouring visible instances share a range so they still go in one draw call.
+    pub fn visible_instances(
+        &self,
+        aabb: &Aabb,
+        sphere: &BoundingSphere,
+        instances: &[InstanceRaw],
+        stats: &mut CullStats,
+    ) -> Vec<Range<u32>> {
+        let mut ranges: Vec<Range<u32>> = Vec::new();
+        for (i, instance) in instances.iter().enumerate() {
+            let i = i as u32;
+            if !self.intersects(aabb, sphere, &instance.model()) {
+                stats.culled += 1;
+                continue;
+            }
+
+            stats.drawn += 1;
+            match ranges.last_mut() {
+                Some(range) if range.end == i => range.end += 1,
+                _ => ranges.push(i..i + 1),
+            }
+        }
+        ranges
+    }
+}
+
+// How many instances the last frame drew and how many it skipped
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CullStats {
+    pub drawn: u32,
+    pub culled: u32,
+}
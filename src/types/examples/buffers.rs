@@ -0,0 +1,78 @@
+use winit::event::WindowEvent;
+
+use super::{Example, ExampleContext, clear_pass, space_held};
+use crate::types::{
+    pipeline_builder::PipelineBuilder,
+    polygon_buffer::PolygonBuffer,
+    vertex_types::{Vertex, colored_vertex::ColoredVertex},
+};
+
+const VERTICES: &[ColoredVertex] = &[
+    ColoredVertex::new([-0.0868241, 0.49240386, 0.0], [0.5, 0.0, 0.5]),
+    ColoredVertex::new([-0.49513406, 0.06958647, 0.0], [0.5, 0.0, 0.5]),
+    ColoredVertex::new([-0.21918549, -0.44939706, 0.0], [0.5, 0.0, 0.5]),
+    ColoredVertex::new([0.35966998, -0.3473291, 0.0], [0.5, 0.0, 0.5]),
+    ColoredVertex::new([0.44147372, 0.2347359, 0.0], [0.5, 0.0, 0.5]),
+];
+
+const INDICES: &[u16] = &[0, 1, 4, 1, 2, 4, 2, 3, 4];
+
+// A pentagon read from vertex and index buffers, with a color per vertex.
+// Holding space swaps it for a polygon generated with more sides.
+pub struct Buffers {
+    pipeline: wgpu::RenderPipeline,
+    polygons: [PolygonBuffer<ColoredVertex>; 2],
+    selected: usize,
+}
+
+impl Example for Buffers {
+    fn init(context: &ExampleContext) -> Self {
+        let pipeline =
+            PipelineBuilder::new("shader.wgsl", include_str!("../resources/shader.wgsl"))
+                .vertex_buffer(ColoredVertex::desc())
+                .color_target(context.config.format)
+                .build(context.device)
+                .unwrap();
+
+        let (vertices, indices) = ColoredVertex::generate_polygon(12, 0.5);
+        Self {
+            pipeline,
+            polygons: [
+                PolygonBuffer::new(context.device, VERTICES, INDICES),
+                PolygonBuffer::new(context.device, &vertices, &indices),
+            ],
+            selected: 0,
+        }
+    }
+
+    fn input(&mut self, event: &WindowEvent) -> bool {
+        let Some(held) = space_held(event) else {
+            return false;
+        };
+        self.selected = held as usize;
+        true
+    }
+
+    fn render(
+        &mut self,
+        _context: &ExampleContext,
+        view: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let polygon = &self.polygons[self.selected];
+        let mut render_pass = clear_pass(
+            encoder,
+            view,
+            wgpu::Color {
+                r: 0.1,
+                g: 0.2,
+                b: 0.3,
+                a: 1.0,
+            },
+        );
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_vertex_buffer(0, polygon.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(polygon.index_buffer.slice(..), polygon.index_format);
+        render_pass.draw_indexed(0..polygon.num_indices, 0, 0..1);
+    }
+}
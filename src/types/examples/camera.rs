@@ -0,0 +1,163 @@
+use std::time::Duration;
+
+use wgpu::util::DeviceExt;
+use winit::event::WindowEvent;
+
+use super::{Example, ExampleContext, clear_pass, shader_source, textures::texture_bind_group};
+use crate::types::{
+    camera_types::{
+        camera::{Camera, Projection},
+        camera_controller::CameraController,
+        camera_uniform::CameraUniform,
+    },
+    pipeline_builder::PipelineBuilder,
+    polygon_buffer::PolygonBuffer,
+    reflection::ShaderReflection,
+    vertex_types::{
+        Vertex,
+        textured_vertex::{INDICES, TexturedVertex, VERTICES},
+    },
+};
+
+// The textured pentagon again, this time in 3D and seen through a camera that
+// WASD or the arrow keys move around
+pub struct CameraStaging {
+    pipeline: wgpu::RenderPipeline,
+    polygon: PolygonBuffer<TexturedVertex>,
+    texture_bind_group: wgpu::BindGroup,
+    camera: Camera,
+    camera_controller: CameraController,
+    camera_uniform: CameraUniform,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+}
+
+impl Example for CameraStaging {
+    fn init(context: &ExampleContext) -> Self {
+        let source = shader_source(
+            "camera_shader.wgsl",
+            include_str!("../resources/camera_shader.wgsl"),
+        );
+        let shader = ShaderReflection::new(&source).unwrap();
+        let layout = |group: u32, label: &str| {
+            context
+                .device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    entries: &shader.bind_group_layout_entries(group).unwrap(),
+                    label: Some(label),
+                })
+        };
+        let texture_bind_group_layout = layout(0, "texture_bind_group_layout");
+        let camera_bind_group_layout = layout(1, "camera_bind_group_layout");
+
+        let camera = Camera {
+            eye: (0.0, 1.0, 2.0).into(),
+            target: (0.0, 0.0, 0.0).into(),
+            up: cgmath::Vector3::unit_y(),
+            aspect: context.config.width as f32 / context.config.height as f32,
+            projection: Projection::Perspective {
+                fovy: cgmath::Deg(45.0),
+                znear: 0.1,
+                zfar: 100.0,
+            },
+            reverse_z: false,
+        };
+        let mut camera_uniform = CameraUniform::new();
+        camera_uniform.update_view_proj(&camera);
+        let camera_buffer = context
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Camera Buffer"),
+                contents: bytemuck::cast_slice(&[camera_uniform]),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+        let camera_bind_group = context
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &camera_bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: camera_buffer.as_entire_binding(),
+                }],
+                label: Some("camera_bind_group"),
+            });
+
+        let pipeline_layout =
+            context
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Camera Pipeline Layout"),
+                    bind_group_layouts: &[&texture_bind_group_layout, &camera_bind_group_layout],
+                    push_constant_ranges: &[],
+                });
+        // Both sides are drawn, since the camera can go round the back
+        let pipeline = PipelineBuilder::new("camera_shader.wgsl", &source)
+            .layout(&pipeline_layout)
+            .vertex_buffer(TexturedVertex::desc())
+            .color_target(context.config.format)
+            .cull_mode(None)
+            .build(context.device)
+            .unwrap();
+
+        Self {
+            pipeline,
+            polygon: PolygonBuffer::new(context.device, VERTICES, INDICES),
+            texture_bind_group: texture_bind_group(
+                context,
+                &texture_bind_group_layout,
+                include_bytes!("../resources/image.png"),
+                "image.png",
+            ),
+            camera,
+            camera_controller: CameraController::new(2.0),
+            camera_uniform,
+            camera_buffer,
+            camera_bind_group,
+        }
+    }
+
+    fn input(&mut self, event: &WindowEvent) -> bool {
+        self.camera_controller.process_events(event)
+    }
+
+    fn update(&mut self, context: &ExampleContext, dt: Duration) {
+        self.camera_controller.update_camera(&mut self.camera, dt);
+        self.camera_uniform.update_view_proj(&self.camera);
+        context.queue.write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::cast_slice(&[self.camera_uniform]),
+        );
+    }
+
+    fn render(
+        &mut self,
+        _context: &ExampleContext,
+        view: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let mut render_pass = clear_pass(
+            encoder,
+            view,
+            wgpu::Color {
+                r: 0.1,
+                g: 0.2,
+                b: 0.3,
+                a: 1.0,
+            },
+        );
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.texture_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.camera_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.polygon.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(
+            self.polygon.index_buffer.slice(..),
+            self.polygon.index_format,
+        );
+        render_pass.draw_indexed(0..self.polygon.num_indices, 0, 0..1);
+    }
+
+    fn resize(&mut self, context: &ExampleContext) {
+        self.camera.aspect = context.config.width as f32 / context.config.height as f32;
+    }
+}
@@ -0,0 +1,51 @@
+use winit::event::WindowEvent;
+
+use super::{Example, ExampleContext, clear_pass};
+
+// The first thing the tutorial draws: nothing, in a color picked by where the
+// cursor is
+pub struct ClearColor {
+    color: wgpu::Color,
+    size: (f64, f64),
+}
+
+impl Example for ClearColor {
+    fn init(context: &ExampleContext) -> Self {
+        Self {
+            color: wgpu::Color {
+                r: 0.1,
+                g: 0.2,
+                b: 0.3,
+                a: 1.0,
+            },
+            size: (context.config.width as f64, context.config.height as f64),
+        }
+    }
+
+    fn input(&mut self, event: &WindowEvent) -> bool {
+        let WindowEvent::CursorMoved { position, .. } = event else {
+            return false;
+        };
+        let (x, y) = (position.x / self.size.0, position.y / self.size.1);
+        self.color = wgpu::Color {
+            r: x,
+            g: y,
+            b: x * y,
+            a: 1.0,
+        };
+        true
+    }
+
+    fn render(
+        &mut self,
+        _context: &ExampleContext,
+        view: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        clear_pass(encoder, view, self.color);
+    }
+
+    fn resize(&mut self, context: &ExampleContext) {
+        self.size = (context.config.width as f64, context.config.height as f64);
+    }
+}
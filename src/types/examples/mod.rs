@@ -0,0 +1,196 @@
+use std::time::Duration;
+
+use winit::event::{ElementState, KeyEvent, WindowEvent};
+use winit::keyboard::{KeyCode, PhysicalKey};
+
+use super::{preprocessor::Preprocessor, shader_library::ShaderLibrary};
+
+pub mod buffers;
+pub mod camera;
+pub mod clear_color;
+pub mod pipeline;
+pub mod textures;
+
+// What an example gets to draw with. State owns all of it, so examples can be
+// swapped without touching the surface.
+pub struct ExampleContext<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+    // The format and size of what render draws into
+    pub config: &'a wgpu::SurfaceConfiguration,
+}
+
+// One of the tutorial's stages, runnable on its own in place of the full scene
+pub trait Example {
+    fn init(context: &ExampleContext) -> Self
+    where
+        Self: Sized;
+
+    // True if the event was used up
+    fn input(&mut self, _event: &WindowEvent) -> bool {
+        false
+    }
+
+    fn update(&mut self, _context: &ExampleContext, _dt: Duration) {}
+
+    // Draws a whole frame into `view`, clearing it first
+    fn render(
+        &mut self,
+        context: &ExampleContext,
+        view: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+    );
+
+    // Called after the surface has been resized to context.config's size
+    fn resize(&mut self, _context: &ExampleContext) {}
+}
+
+type Init = fn(&ExampleContext) -> Box<dyn Example>;
+
+pub struct ExampleInfo {
+    pub name: &'static str,
+    pub description: &'static str,
+    // None for the full scene, which State draws itself
+    init: Option<Init>,
+}
+
+impl ExampleInfo {
+    pub fn is_scene(&self) -> bool {
+        self.init.is_none()
+    }
+
+    pub fn init(&self, context: &ExampleContext) -> Option<Box<dyn Example>> {
+        self.init.map(|init| init(context))
+    }
+}
+
+// In the order the tutorial builds them up. The number keys pick them, starting at 1.
+pub const EXAMPLES: &[ExampleInfo] = &[
+    ExampleInfo {
+        name: "clear_color",
+        description: "Clears the surface to a color that follows the cursor",
+        init: Some(boxed::<clear_color::ClearColor>),
+    },
+    ExampleInfo {
+        name: "pipeline",
+        description: "A triangle made up in the vertex shader, hold space to switch pipelines",
+        init: Some(boxed::<pipeline::Pipeline>),
+    },
+    ExampleInfo {
+        name: "buffers",
+        description: "A pentagon from vertex and index buffers, hold space for a generated polygon",
+        init: Some(boxed::<buffers::Buffers>),
+    },
+    ExampleInfo {
+        name: "textures",
+        description: "The pentagon with a texture on it, hold space to switch images",
+        init: Some(boxed::<textures::Textures>),
+    },
+    ExampleInfo {
+        name: "camera",
+        description: "The textured pentagon seen through a camera, WASD or the arrow keys move it",
+        init: Some(boxed::<camera::CameraStaging>),
+    },
+    ExampleInfo {
+        name: "scene",
+        description: "Everything together: instancing, lights, shadows and the rest",
+        init: None,
+    },
+];
+
+// Looks an example up by name, or by its number as the hotkeys count them
+pub fn find(name: &str) -> Option<usize> {
+    match name.parse::<usize>() {
+        Ok(number) => (1..=EXAMPLES.len()).contains(&number).then(|| number - 1),
+        Err(_) => EXAMPLES.iter().position(|example| example.name == name),
+    }
+}
+
+// The example a number key picks, if there is one
+pub fn hotkey(event: &WindowEvent) -> Option<usize> {
+    let WindowEvent::KeyboardInput {
+        event:
+            KeyEvent {
+                state: ElementState::Pressed,
+                physical_key: PhysicalKey::Code(code),
+                repeat: false,
+                ..
+            },
+        ..
+    } = event
+    else {
+        return None;
+    };
+    let index = [
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ]
+    .iter()
+    .position(|digit| digit == code)?;
+    (index < EXAMPLES.len()).then_some(index)
+}
+
+fn boxed<E: Example + 'static>(context: &ExampleContext) -> Box<dyn Example> {
+    Box::new(E::init(context))
+}
+
+// Whether space is being held, for the examples that switch something while it is.
+// None for every other event.
+fn space_held(event: &WindowEvent) -> Option<bool> {
+    match event {
+        WindowEvent::KeyboardInput {
+            event:
+                KeyEvent {
+                    state,
+                    physical_key: PhysicalKey::Code(KeyCode::Space),
+                    ..
+                },
+            ..
+        } => Some(*state == ElementState::Pressed),
+        _ => None,
+    }
+}
+
+// An example's own shader, with its #includes looked up in the built in library
+fn shader_source(name: &str, source: &'static str) -> String {
+    let library = ShaderLibrary::embedded();
+    Preprocessor::new(|file| {
+        if file == name {
+            Ok(source.into())
+        } else {
+            library.source(file)
+        }
+    })
+    .process(name)
+    .unwrap_or_else(|e| panic!("the built in {name} is broken: {e:#}"))
+    .source
+}
+
+// A pass over the whole of `view` that starts by clearing it to `color`
+fn clear_pass<'e>(
+    encoder: &'e mut wgpu::CommandEncoder,
+    view: &wgpu::TextureView,
+    color: wgpu::Color,
+) -> wgpu::RenderPass<'e> {
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Example Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(color),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        occlusion_query_set: None,
+        timestamp_writes: None,
+    })
+}
@@ -0,0 +1,64 @@
+use winit::event::WindowEvent;
+
+use super::{Example, ExampleContext, clear_pass, space_held};
+use crate::types::pipeline_builder::PipelineBuilder;
+
+// A triangle with no buffers at all, the vertex shader works its corners out
+// from the vertex index. Holding space draws it with a second pipeline that
+// colors it by position instead.
+pub struct Pipeline {
+    pipelines: [wgpu::RenderPipeline; 2],
+    selected: usize,
+}
+
+impl Example for Pipeline {
+    fn init(context: &ExampleContext) -> Self {
+        let pipeline = |label, source| {
+            PipelineBuilder::new(label, source)
+                .color_target(context.config.format)
+                .build(context.device)
+                .unwrap()
+        };
+        Self {
+            pipelines: [
+                pipeline(
+                    "triangle_shader.wgsl",
+                    include_str!("../resources/triangle_shader.wgsl"),
+                ),
+                pipeline(
+                    "challenge_3.wgsl",
+                    include_str!("../resources/challenge_3.wgsl"),
+                ),
+            ],
+            selected: 0,
+        }
+    }
+
+    fn input(&mut self, event: &WindowEvent) -> bool {
+        let Some(held) = space_held(event) else {
+            return false;
+        };
+        self.selected = held as usize;
+        true
+    }
+
+    fn render(
+        &mut self,
+        _context: &ExampleContext,
+        view: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let mut render_pass = clear_pass(
+            encoder,
+            view,
+            wgpu::Color {
+                r: 0.1,
+                g: 0.2,
+                b: 0.3,
+                a: 1.0,
+            },
+        );
+        render_pass.set_pipeline(&self.pipelines[self.selected]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
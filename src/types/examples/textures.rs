@@ -0,0 +1,144 @@
+use winit::event::WindowEvent;
+
+use super::{Example, ExampleContext, clear_pass, shader_source, space_held};
+use crate::types::{
+    pipeline_builder::PipelineBuilder,
+    polygon_buffer::PolygonBuffer,
+    reflection::ShaderReflection,
+    texture::{Texture, TextureOptions},
+    vertex_types::{
+        Vertex,
+        textured_vertex::{INDICES, TexturedVertex, VERTICES},
+    },
+};
+
+// The pentagon again, with an image mapped onto it. Holding space switches to
+// another image, which only needs a different bind group.
+pub struct Textures {
+    pipeline: wgpu::RenderPipeline,
+    polygon: PolygonBuffer<TexturedVertex>,
+    bind_groups: [wgpu::BindGroup; 2],
+    selected: usize,
+}
+
+impl Example for Textures {
+    fn init(context: &ExampleContext) -> Self {
+        let source = shader_source(
+            "textured_shader.wgsl",
+            include_str!("../resources/textured_shader.wgsl"),
+        );
+        let entries = ShaderReflection::new(&source)
+            .unwrap()
+            .bind_group_layout_entries(0)
+            .unwrap();
+        let layout = context
+            .device
+            .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &entries,
+                label: Some("texture_bind_group_layout"),
+            });
+
+        let pipeline_layout =
+            context
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: Some("Textures Pipeline Layout"),
+                    bind_group_layouts: &[&layout],
+                    push_constant_ranges: &[],
+                });
+        let pipeline = PipelineBuilder::new("textured_shader.wgsl", &source)
+            .layout(&pipeline_layout)
+            .vertex_buffer(TexturedVertex::desc())
+            .color_target(context.config.format)
+            .build(context.device)
+            .unwrap();
+
+        Self {
+            pipeline,
+            polygon: PolygonBuffer::new(context.device, VERTICES, INDICES),
+            bind_groups: [
+                texture_bind_group(
+                    context,
+                    &layout,
+                    include_bytes!("../resources/image.png"),
+                    "image.png",
+                ),
+                texture_bind_group(
+                    context,
+                    &layout,
+                    include_bytes!("../resources/challenge_image.jpeg"),
+                    "challenge_image.jpeg",
+                ),
+            ],
+            selected: 0,
+        }
+    }
+
+    fn input(&mut self, event: &WindowEvent) -> bool {
+        let Some(held) = space_held(event) else {
+            return false;
+        };
+        self.selected = held as usize;
+        true
+    }
+
+    fn render(
+        &mut self,
+        _context: &ExampleContext,
+        view: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let mut render_pass = clear_pass(
+            encoder,
+            view,
+            wgpu::Color {
+                r: 0.1,
+                g: 0.2,
+                b: 0.3,
+                a: 1.0,
+            },
+        );
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_groups[self.selected], &[]);
+        render_pass.set_vertex_buffer(0, self.polygon.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(
+            self.polygon.index_buffer.slice(..),
+            self.polygon.index_format,
+        );
+        render_pass.draw_indexed(0..self.polygon.num_indices, 0, 0..1);
+    }
+}
+
+// The bind group only keeps the texture's view and sampler alive, so the Texture
+// itself can go
+pub(super) fn texture_bind_group(
+    context: &ExampleContext,
+    layout: &wgpu::BindGroupLayout,
+    bytes: &[u8],
+    label: &str,
+) -> wgpu::BindGroup {
+    let texture = Texture::from_bytes(
+        context.device,
+        context.queue,
+        bytes,
+        label,
+        TextureOptions::DEFAULT,
+    )
+    .unwrap();
+    context
+        .device
+        .create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&texture.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&texture.sampler),
+                },
+            ],
+            label: Some(label),
+        })
+}
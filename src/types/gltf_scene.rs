@@ -0,0 +1,697 @@
+use std::{ops::Range, path::Path};
+
+use anyhow::*;
+use cgmath::SquareMatrix;
+use wgpu::util::DeviceExt;
+
+use super::{
+    culling::{Aabb, BoundingSphere},
+    instance_buffer::InstanceBuffer,
+    model::{compute_normals, white_texture},
+    polygon_buffer::PolygonBuffer,
+    texture,
+    vertex_types::{instance::InstanceRaw, pbr_vertex::PbrVertex},
+};
+
+// A glTF file as it comes out of the importer, before anything is uploaded to the GPU.
+// Nodes, meshes, materials and images keep the indices they have in the file.
+pub struct SceneData {
+    pub nodes: Vec<Node>,
+    // Top level nodes of the default scene
+    pub roots: Vec<usize>,
+    pub meshes: Vec<MeshData>,
+    pub materials: Vec<PbrMaterialData>,
+    pub images: Vec<image::RgbaImage>,
+}
+
+pub struct Node {
+    pub name: Option<String>,
+    // Relative to the parent node
+    pub transform: cgmath::Matrix4<f32>,
+    pub mesh: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+pub struct MeshData {
+    pub name: Option<String>,
+    pub primitives: Vec<PrimitiveData>,
+}
+
+pub struct PrimitiveData {
+    pub vertices: Vec<PbrVertex>,
+    pub indices: Vec<u32>,
+    pub material: Option<usize>,
+}
+
+// The metallic-roughness material model. Textures are indices into SceneData::images.
+pub struct PbrMaterialData {
+    pub name: Option<String>,
+    pub base_color_factor: [f32; 4],
+    pub base_color_texture: Option<usize>,
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    // Metalness in the blue channel, roughness in the green one
+    pub metallic_roughness_texture: Option<usize>,
+    pub normal_texture: Option<usize>,
+    pub normal_scale: f32,
+    pub occlusion_texture: Option<usize>,
+    pub emissive_factor: [f32; 3],
+    pub emissive_texture: Option<usize>,
+}
+
+impl SceneData {
+    // Buffers and images that live next to a .gltf file are loaded from disk.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let (document, buffers, images) =
+            gltf::import(path).with_context(|| format!("couldn't import {}", path.display()))?;
+        Self::from_import(document, buffers, images)
+    }
+
+    // A .glb or .gltf that's already in memory. Any buffers or images that
+    // aren't embedded in it are looked up in `base_dir`.
+    pub fn from_slice(bytes: &[u8], base_dir: &Path) -> Result<Self> {
+        let gltf::Gltf { document, blob } =
+            gltf::Gltf::from_slice(bytes).context("couldn't parse glTF")?;
+        let buffers = gltf::import_buffers(&document, Some(base_dir), blob)
+            .context("couldn't load glTF buffers")?;
+        let images = gltf::import_images(&document, Some(base_dir), &buffers)
+            .context("couldn't load glTF images")?;
+        Self::from_import(document, buffers, images)
+    }
+
+    fn from_import(
+        document: gltf::Document,
+        buffers: Vec<gltf::buffer::Data>,
+        images: Vec<gltf::image::Data>,
+    ) -> Result<Self> {
+        let nodes = document
+            .nodes()
+            .map(|node| Node {
+                name: node.name().map(str::to_string),
+                transform: node.transform().matrix().into(),
+                mesh: node.mesh().map(|m| m.index()),
+                children: node.children().map(|c| c.index()).collect(),
+            })
+            .collect();
+
+        let roots = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+            .map(|scene| scene.nodes().map(|n| n.index()).collect())
+            .unwrap_or_default();
+
+        let meshes = document
+            .meshes()
+            .map(|mesh| {
+                let primitives = mesh
+                    .primitives()
+                    .map(|p| read_primitive(&p, &buffers))
+                    .collect::<Result<Vec<_>>>()
+                    .with_context(|| format!("in mesh {}", mesh.name().unwrap_or("<unnamed>")))?;
+                Ok(MeshData {
+                    name: mesh.name().map(str::to_string),
+                    primitives,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let materials = document
+            .materials()
+            .map(|m| {
+                let pbr = m.pbr_metallic_roughness();
+                PbrMaterialData {
+                    name: m.name().map(str::to_string),
+                    base_color_factor: pbr.base_color_factor(),
+                    base_color_texture: pbr
+                        .base_color_texture()
+                        .map(|t| t.texture().source().index()),
+                    metallic_factor: pbr.metallic_factor(),
+                    roughness_factor: pbr.roughness_factor(),
+                    metallic_roughness_texture: pbr
+                        .metallic_roughness_texture()
+                        .map(|t| t.texture().source().index()),
+                    normal_texture: m.normal_texture().map(|t| t.texture().source().index()),
+                    normal_scale: m.normal_texture().map_or(1.0, |t| t.scale()),
+                    occlusion_texture: m.occlusion_texture().map(|t| t.texture().source().index()),
+                    emissive_factor: m.emissive_factor(),
+                    emissive_texture: m.emissive_texture().map(|t| t.texture().source().index()),
+                }
+            })
+            .collect();
+
+        let images = images.iter().map(to_rgba).collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            nodes,
+            roots,
+            meshes,
+            materials,
+            images,
+        })
+    }
+
+    // Every mesh that is reachable from the scene roots, with its world transform.
+    pub fn mesh_instances(&self) -> Vec<(usize, cgmath::Matrix4<f32>)> {
+        let mut instances = Vec::new();
+        // (node, parent's world transform); depth is bounded by the number of nodes
+        // so a malformed file with a cycle can't loop forever
+        let mut stack = self
+            .roots
+            .iter()
+            .map(|r| (*r, cgmath::Matrix4::identity(), 0))
+            .collect::<Vec<_>>();
+        while let Some((index, parent, depth)) = stack.pop() {
+            let Some(node) = self.nodes.get(index) else {
+                continue;
+            };
+            if depth > self.nodes.len() {
+                continue;
+            }
+
+            let world = parent * node.transform;
+            if let Some(mesh) = node.mesh {
+                instances.push((mesh, world));
+            }
+            stack.extend(node.children.iter().map(|c| (*c, world, depth + 1)));
+        }
+
+        instances
+    }
+}
+
+fn read_primitive(
+    primitive: &gltf::Primitive,
+    buffers: &[gltf::buffer::Data],
+) -> Result<PrimitiveData> {
+    if primitive.mode() != gltf::mesh::Mode::Triangles {
+        bail!(
+            "primitive {} uses {:?}, only triangle lists are supported",
+            primitive.index(),
+            primitive.mode()
+        );
+    }
+
+    let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|data| &data[..]));
+
+    let positions = reader
+        .read_positions()
+        .with_context(|| format!("primitive {} has no positions", primitive.index()))?
+        .collect::<Vec<_>>();
+
+    let indices = match reader.read_indices() {
+        Some(indices) => indices.into_u32().collect::<Vec<_>>(),
+        None => (0..positions.len() as u32).collect(),
+    };
+    if indices.len() % 3 != 0 {
+        bail!(
+            "primitive {} has {} indices, which can't be split into triangles",
+            primitive.index(),
+            indices.len()
+        );
+    }
+    if let Some(index) = indices.iter().find(|i| **i as usize >= positions.len()) {
+        bail!(
+            "primitive {} references vertex {index}, but only has {}",
+            primitive.index(),
+            positions.len()
+        );
+    }
+
+    let tex_coords = match reader.read_tex_coords(0) {
+        Some(tex_coords) => tex_coords.into_f32().collect(),
+        None => vec![[0.0, 0.0]; positions.len()],
+    };
+    let normals = match reader.read_normals() {
+        Some(normals) => normals.collect(),
+        None => compute_normals(&positions, &indices),
+    };
+    if tex_coords.len() != positions.len() || normals.len() != positions.len() {
+        bail!(
+            "primitive {} has attributes with different numbers of vertices",
+            primitive.index()
+        );
+    }
+    let tangents = match reader.read_tangents() {
+        Some(tangents) => tangents.collect(),
+        None => compute_tangents(&positions, &tex_coords, &normals, &indices),
+    };
+
+    let vertices = (0..positions.len())
+        .map(|i| PbrVertex {
+            position: positions[i],
+            tex_coords: tex_coords[i],
+            normal: normals[i],
+            tangent: tangents.get(i).copied().unwrap_or([1.0, 0.0, 0.0, 1.0]),
+        })
+        .collect();
+
+    Ok(PrimitiveData {
+        vertices,
+        indices,
+        material: primitive.material().index(),
+    })
+}
+
+// Tangents for primitives that don't have any, lined up with the direction u
+// increases in across each triangle.
+fn compute_tangents(
+    positions: &[[f32; 3]],
+    tex_coords: &[[f32; 2]],
+    normals: &[[f32; 3]],
+    indices: &[u32],
+) -> Vec<[f32; 4]> {
+    use cgmath::{InnerSpace, Vector2, Vector3, Zero};
+
+    let mut tangents = vec![Vector3::<f32>::zero(); positions.len()];
+    let mut bitangents = vec![Vector3::<f32>::zero(); positions.len()];
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|i| i as usize);
+        let [p0, p1, p2] = [a, b, c].map(|i| Vector3::from(positions[i]));
+        let [uv0, uv1, uv2] = [a, b, c].map(|i| Vector2::from(tex_coords[i]));
+
+        let (edge1, edge2) = (p1 - p0, p2 - p0);
+        let (duv1, duv2) = (uv1 - uv0, uv2 - uv0);
+        let det = duv1.x * duv2.y - duv2.x * duv1.y;
+        if det.abs() < f32::EPSILON {
+            continue;
+        }
+
+        let r = 1.0 / det;
+        let tangent = (edge1 * duv2.y - edge2 * duv1.y) * r;
+        let bitangent = (edge2 * duv1.x - edge1 * duv2.x) * r;
+        for i in [a, b, c] {
+            tangents[i] += tangent;
+            bitangents[i] += bitangent;
+        }
+    }
+
+    (0..positions.len())
+        .map(|i| {
+            let normal = Vector3::from(normals[i]);
+            // Gram-Schmidt, so the tangent is perpendicular to the normal
+            let tangent = tangents[i] - normal * normal.dot(tangents[i]);
+            if tangent.magnitude2() < f32::EPSILON {
+                return [1.0, 0.0, 0.0, 1.0];
+            }
+            let tangent = tangent.normalize();
+            let handedness = if normal.cross(tangent).dot(bitangents[i]) < 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+            [tangent.x, tangent.y, tangent.z, handedness]
+        })
+        .collect()
+}
+
+fn to_rgba(data: &gltf::image::Data) -> Result<image::RgbaImage> {
+    use gltf::image::Format;
+
+    // (channels, bytes per channel)
+    let (channels, size) = match data.format {
+        Format::R8 => (1, 1),
+        Format::R8G8 => (2, 1),
+        Format::R8G8B8 => (3, 1),
+        Format::R8G8B8A8 => (4, 1),
+        Format::R16 => (1, 2),
+        Format::R16G16 => (2, 2),
+        Format::R16G16B16 => (3, 2),
+        Format::R16G16B16A16 => (4, 2),
+        Format::R32G32B32FLOAT => (3, 4),
+        Format::R32G32B32A32FLOAT => (4, 4),
+    };
+
+    let channel = |bytes: &[u8]| -> u8 {
+        match bytes.len() {
+            1 => bytes[0],
+            2 => (u16::from_ne_bytes([bytes[0], bytes[1]]) >> 8) as u8,
+            _ => (f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]).clamp(0.0, 1.0)
+                * 255.0)
+                .round() as u8,
+        }
+    };
+
+    let pixels = data
+        .pixels
+        .chunks_exact(channels * size)
+        .flat_map(|pixel| {
+            let c = pixel.chunks_exact(size).map(channel).collect::<Vec<_>>();
+            // One or two channels are grayscale (with alpha), as decoded by the importer
+            match channels {
+                1 => [c[0], c[0], c[0], 255],
+                2 => [c[0], c[0], c[0], c[1]],
+                3 => [c[0], c[1], c[2], 255],
+                _ => [c[0], c[1], c[2], c[3]],
+            }
+        })
+        .collect();
+
+    image::RgbaImage::from_raw(data.width, data.height, pixels)
+        .context("image data does not match its dimensions")
+}
+
+pub struct Primitive {
+    pub polygon_buffer: PolygonBuffer<PbrVertex>,
+    pub material: Option<usize>,
+}
+
+pub struct GltfMesh {
+    pub name: Option<String>,
+    pub primitives: Vec<Primitive>,
+    // Around every primitive together, since they're culled as one
+    pub aabb: Aabb,
+    pub bounding_sphere: BoundingSphere,
+}
+
+// The factors of a PbrMaterial that the glTF shader reads, padded out to the
+// size of the struct in wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct PbrMaterialUniform {
+    pub base_color_factor: [f32; 4],
+    pub emissive_factor: [f32; 3],
+    _padding: f32,
+}
+
+pub struct PbrMaterial {
+    pub name: Option<String>,
+    pub base_color_texture: texture::Texture,
+    // The base color texture and its sampler, a PbrMaterialUniform, then the
+    // emissive texture and its sampler
+    pub bind_group: wgpu::BindGroup,
+    pub base_color_factor: [f32; 4],
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    pub metallic_roughness_texture: Option<texture::Texture>,
+    pub normal_texture: Option<texture::Texture>,
+    pub normal_scale: f32,
+    pub occlusion_texture: Option<texture::Texture>,
+    pub emissive_factor: [f32; 3],
+    // Multiplied by emissive_factor, white when the material has none
+    pub emissive_texture: texture::Texture,
+}
+
+impl PbrMaterial {
+    fn bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        name: &str,
+        base_color_texture: &texture::Texture,
+        base_color_factor: [f32; 4],
+        emissive_texture: &texture::Texture,
+        emissive_factor: [f32; 3],
+    ) -> wgpu::BindGroup {
+        let uniform = PbrMaterialUniform {
+            base_color_factor,
+            emissive_factor,
+            _padding: 0.0,
+        };
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(name),
+            contents: bytemuck::cast_slice(&[uniform]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&base_color_texture.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&base_color_texture.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(&emissive_texture.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::Sampler(&emissive_texture.sampler),
+                },
+            ],
+            label: Some(name),
+        })
+    }
+}
+
+pub struct GltfScene {
+    pub nodes: Vec<Node>,
+    pub roots: Vec<usize>,
+    pub meshes: Vec<GltfMesh>,
+    pub materials: Vec<PbrMaterial>,
+    // One instance buffer per mesh holding the world transform of every node that uses it
+    instances: Vec<(usize, InstanceBuffer)>,
+    default_material: PbrMaterial,
+}
+
+impl GltfScene {
+    // `layout` is the material bind group of the glTF shader: the base color
+    // texture and its sampler, a PbrMaterialUniform, then the emissive texture
+    // and its sampler.
+    pub fn load(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        path: impl AsRef<Path>,
+        layout: &wgpu::BindGroupLayout,
+    ) -> Result<Self> {
+        Self::from_data(device, queue, SceneData::load(path)?, layout)
+    }
+
+    pub fn from_data(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        data: SceneData,
+        layout: &wgpu::BindGroupLayout,
+    ) -> Result<Self> {
+        let upload = |index: Option<usize>,
+                      color_space: texture::ColorSpace|
+         -> Result<Option<texture::Texture>> {
+            let Some(index) = index else { return Ok(None) };
+            let img = data
+                .images
+                .get(index)
+                .with_context(|| format!("image {index} does not exist"))?;
+            let img = image::DynamicImage::ImageRgba8(img.clone());
+            // glTF samplers repeat unless they say otherwise
+            let options = texture::TextureOptions {
+                color_space,
+                address_mode_u: wgpu::AddressMode::Repeat,
+                address_mode_v: wgpu::AddressMode::Repeat,
+                ..texture::TextureOptions::TRILINEAR
+            };
+            Ok(Some(texture::Texture::from_image(
+                device,
+                queue,
+                &img,
+                Some("glTF image"),
+                options,
+            )?))
+        };
+
+        let materials = data
+            .materials
+            .iter()
+            .map(|m| {
+                // Only color textures are sRGB, everything else is data
+                let (srgb, linear) = (texture::ColorSpace::Srgb, texture::ColorSpace::Linear);
+                let base_color_texture = match upload(m.base_color_texture, srgb)? {
+                    Some(texture) => texture,
+                    None => white_texture(device, queue)?,
+                };
+                let emissive_texture = match upload(m.emissive_texture, srgb)? {
+                    Some(texture) => texture,
+                    None => white_texture(device, queue)?,
+                };
+                let name = m.name.as_deref().unwrap_or("glTF material");
+                let bind_group = PbrMaterial::bind_group(
+                    device,
+                    layout,
+                    name,
+                    &base_color_texture,
+                    m.base_color_factor,
+                    &emissive_texture,
+                    m.emissive_factor,
+                );
+
+                Ok(PbrMaterial {
+                    name: m.name.clone(),
+                    base_color_texture,
+                    bind_group,
+                    base_color_factor: m.base_color_factor,
+                    metallic_factor: m.metallic_factor,
+                    roughness_factor: m.roughness_factor,
+                    metallic_roughness_texture: upload(m.metallic_roughness_texture, linear)?,
+                    normal_texture: upload(m.normal_texture, linear)?,
+                    normal_scale: m.normal_scale,
+                    occlusion_texture: upload(m.occlusion_texture, linear)?,
+                    emissive_factor: m.emissive_factor,
+                    emissive_texture,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut transforms = vec![Vec::new(); data.meshes.len()];
+        for (mesh, world) in data.mesh_instances() {
+            let transforms = transforms
+                .get_mut(mesh)
+                .with_context(|| format!("mesh {mesh} does not exist"))?;
+            transforms.push(InstanceRaw::from(world));
+        }
+        let instances = transforms
+            .into_iter()
+            .enumerate()
+            .filter(|(_, t)| !t.is_empty())
+            .map(|(mesh, t)| (mesh, InstanceBuffer::from_raw(device, &t)))
+            .collect();
+
+        let meshes = data
+            .meshes
+            .into_iter()
+            .map(|mesh| {
+                let positions = mesh
+                    .primitives
+                    .iter()
+                    .flat_map(|p| p.vertices.iter().map(|v| v.position))
+                    .collect::<Vec<_>>();
+                GltfMesh {
+                    name: mesh.name,
+                    primitives: mesh
+                        .primitives
+                        .into_iter()
+                        .map(|p| Primitive {
+                            polygon_buffer: PolygonBuffer::with_u32_indices(
+                                device,
+                                &p.vertices,
+                                &p.indices,
+                            ),
+                            material: p.material,
+                        })
+                        .collect(),
+                    aabb: Aabb::from_points(positions.iter().copied()),
+                    bounding_sphere: BoundingSphere::from_points(&positions),
+                }
+            })
+            .collect();
+
+        let (white, emissive) = (white_texture(device, queue)?, white_texture(device, queue)?);
+        let default_material = PbrMaterial {
+            name: None,
+            bind_group: PbrMaterial::bind_group(
+                device, layout, "default", &white, [1.0; 4], &emissive, [0.0; 3],
+            ),
+            base_color_texture: white,
+            base_color_factor: [1.0; 4],
+            metallic_factor: 1.0,
+            roughness_factor: 1.0,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+            normal_scale: 1.0,
+            occlusion_texture: None,
+            emissive_factor: [0.0; 3],
+            emissive_texture: emissive,
+        };
+
+        Ok(Self {
+            nodes: data.nodes,
+            roots: data.roots,
+            meshes,
+            materials,
+            instances,
+            default_material,
+        })
+    }
+
+    pub fn material_for(&self, primitive: &Primitive) -> &PbrMaterial {
+        primitive
+            .material
+            .and_then(|id| self.materials.get(id))
+            .unwrap_or(&self.default_material)
+    }
+
+    // Each mesh that's drawn, with the world transforms of the nodes that use it.
+    // draw_gltf_scene takes the instances to draw in this order.
+    pub fn mesh_instances(&self) -> impl Iterator<Item = (&GltfMesh, &InstanceBuffer)> {
+        self.instances
+            .iter()
+            .map(|(mesh, instances)| (&self.meshes[*mesh], instances))
+    }
+}
+
+// Draws every mesh in the scene at the world transforms of the nodes that use
+// it. The pipeline needs PbrVertex at slot 0, InstanceRaw at slot 1 and the
+// material at group 0; anything else must already be bound.
+// draw_gltf_scene only draws the `visible` ranges of instances, one list of them
+// for each of the scene's mesh_instances, as Frustum::visible_instances gives them.
+// draw_gltf_scene_depth draws every instance and leaves the materials out, for
+// depth only passes that have something else at group 0.
+pub trait DrawGltfScene<'a> {
+    fn draw_primitive_instanced(
+        &mut self,
+        primitive: &'a Primitive,
+        material: &'a PbrMaterial,
+        instances: Range<u32>,
+    );
+    fn draw_gltf_scene(&mut self, scene: &'a GltfScene, visible: &[Vec<Range<u32>>]);
+    fn draw_gltf_scene_depth(&mut self, scene: &'a GltfScene);
+}
+
+impl<'a> DrawGltfScene<'a> for wgpu::RenderPass<'a> {
+    fn draw_primitive_instanced(
+        &mut self,
+        primitive: &'a Primitive,
+        material: &'a PbrMaterial,
+        instances: Range<u32>,
+    ) {
+        self.set_bind_group(0, &material.bind_group, &[]);
+        self.set_vertex_buffer(0, primitive.polygon_buffer.vertex_buffer.slice(..));
+        self.set_index_buffer(
+            primitive.polygon_buffer.index_buffer.slice(..),
+            primitive.polygon_buffer.index_format,
+        );
+        self.draw_indexed(0..primitive.polygon_buffer.num_indices, 0, instances);
+    }
+
+    fn draw_gltf_scene(&mut self, scene: &'a GltfScene, visible: &[Vec<Range<u32>>]) {
+        for ((mesh, instances), ranges) in scene.mesh_instances().zip(visible) {
+            if ranges.is_empty() {
+                continue;
+            }
+            self.set_vertex_buffer(1, instances.buffer.slice(..));
+            for primitive in &mesh.primitives {
+                for range in ranges {
+                    self.draw_primitive_instanced(
+                        primitive,
+                        scene.material_for(primitive),
+                        range.clone(),
+                    );
+                }
+            }
+        }
+    }
+
+    fn draw_gltf_scene_depth(&mut self, scene: &'a GltfScene) {
+        for (mesh, instances) in &scene.instances {
+            self.set_vertex_buffer(1, instances.buffer.slice(..));
+            for primitive in &scene.meshes[*mesh].primitives {
+                self.set_vertex_buffer(0, primitive.polygon_buffer.vertex_buffer.slice(..));
+                self.set_index_buffer(
+                    primitive.polygon_buffer.index_buffer.slice(..),
+                    primitive.polygon_buffer.index_format,
+                );
+                self.draw_indexed(
+                    0..primitive.polygon_buffer.num_indices,
+                    0,
+                    0..instances.num_instances,
+                );
+            }
+        }
+    }
+}
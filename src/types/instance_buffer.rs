@@ -0,0 +1,50 @@
+use wgpu::{Device, util::DeviceExt};
+
+use super::vertex_types::instance::{Instance, InstanceRaw};
+
+pub struct InstanceBuffer {
+    pub buffer: wgpu::Buffer,
+    pub num_instances: u32,
+    capacity: usize,
+    // What's in the buffer, kept around so instances can be culled on the CPU
+    instances: Vec<InstanceRaw>,
+}
+
+impl InstanceBuffer {
+    pub fn new(device: &Device, instances: &[Instance]) -> Self {
+        let raw = instances.iter().map(Instance::to_raw).collect::<Vec<_>>();
+        Self::from_raw(device, &raw)
+    }
+
+    pub fn from_raw(device: &Device, instances: &[InstanceRaw]) -> Self {
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Instance Buffer"),
+            contents: bytemuck::cast_slice(instances),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        });
+
+        Self {
+            buffer,
+            num_instances: instances.len() as u32,
+            capacity: instances.len(),
+            instances: instances.to_vec(),
+        }
+    }
+
+    // Uploads new instance data, only reallocating when the buffer is too small.
+    pub fn update(&mut self, device: &Device, queue: &wgpu::Queue, instances: &[Instance]) {
+        let raw = instances.iter().map(Instance::to_raw).collect::<Vec<_>>();
+        if raw.len() > self.capacity {
+            *self = Self::from_raw(device, &raw);
+            return;
+        }
+
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&raw));
+        self.num_instances = instances.len() as u32;
+        self.instances = raw;
+    }
+
+    pub fn instances(&self) -> &[InstanceRaw] {
+        &self.instances
+    }
+}
@@ -0,0 +1,128 @@
+use cgmath::InnerSpace;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LightKind {
+    // Shines in every direction from `position`
+    Point,
+    // Shines from `position` along `direction`, inside a cone
+    Spot {
+        inner_angle: cgmath::Deg<f32>,
+        outer_angle: cgmath::Deg<f32>,
+    },
+    // Infinitely far away, shines along `direction` everywhere (like the sun)
+    Directional,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Light {
+    pub kind: LightKind,
+    pub position: cgmath::Point3<f32>,
+    pub direction: cgmath::Vector3<f32>,
+    pub color: [f32; 3],
+    pub intensity: f32,
+    // Point and spot lights fade out to nothing at this distance
+    pub range: f32,
+}
+
+impl Light {
+    pub fn point(
+        position: cgmath::Point3<f32>,
+        color: [f32; 3],
+        intensity: f32,
+        range: f32,
+    ) -> Self {
+        Self {
+            kind: LightKind::Point,
+            position,
+            direction: -cgmath::Vector3::unit_y(),
+            color,
+            intensity,
+            range,
+        }
+    }
+
+    // `inner_angle` and `outer_angle` are measured from `direction`; the light fades
+    // out between the two.
+    pub fn spot(
+        position: cgmath::Point3<f32>,
+        direction: cgmath::Vector3<f32>,
+        inner_angle: cgmath::Deg<f32>,
+        outer_angle: cgmath::Deg<f32>,
+        color: [f32; 3],
+        intensity: f32,
+        range: f32,
+    ) -> Self {
+        Self {
+            kind: LightKind::Spot {
+                inner_angle,
+                outer_angle,
+            },
+            position,
+            direction,
+            color,
+            intensity,
+            range,
+        }
+    }
+
+    pub fn directional(direction: cgmath::Vector3<f32>, color: [f32; 3], intensity: f32) -> Self {
+        Self {
+            kind: LightKind::Directional,
+            position: cgmath::Point3::new(0.0, 0.0, 0.0),
+            direction,
+            color,
+            intensity,
+            range: f32::INFINITY,
+        }
+    }
+
+    pub fn to_raw(&self) -> LightRaw {
+        let (kind, inner_cone_cos, outer_cone_cos) = match self.kind {
+            LightKind::Point => (LightRaw::POINT, -1.0, -1.0),
+            LightKind::Spot {
+                inner_angle,
+                outer_angle,
+            } => (
+                LightRaw::SPOT,
+                cgmath::Angle::cos(inner_angle),
+                cgmath::Angle::cos(outer_angle),
+            ),
+            LightKind::Directional => (LightRaw::DIRECTIONAL, -1.0, -1.0),
+        };
+
+        LightRaw {
+            position: self.position.into(),
+            kind,
+            direction: self.direction.normalize().into(),
+            range: self.range,
+            color: self.color,
+            intensity: self.intensity,
+            inner_cone_cos,
+            outer_cone_cos,
+            _padding: [0.0; 2],
+        }
+    }
+}
+
+// How a light is laid out on the GPU. Matches the Light struct in the shaders;
+// every vec3 is followed by a scalar so nothing needs extra padding, and the
+// size is a multiple of 16 so it also works as a uniform array element.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct LightRaw {
+    pub position: [f32; 3],
+    pub kind: u32,
+    pub direction: [f32; 3],
+    pub range: f32,
+    pub color: [f32; 3],
+    pub intensity: f32,
+    pub inner_cone_cos: f32,
+    pub outer_cone_cos: f32,
+    _padding: [f32; 2],
+}
+
+impl LightRaw {
+    pub const POINT: u32 = 0;
+    pub const SPOT: u32 = 1;
+    pub const DIRECTIONAL: u32 = 2;
+}
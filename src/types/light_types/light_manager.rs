@@ -0,0 +1,245 @@
+use wgpu::util::DeviceExt;
+
+use super::light::{Light, LightRaw};
+
+// How many lights fit in the uniform array used when storage buffers aren't available.
+pub const MAX_UNIFORM_LIGHTS: usize = 16;
+
+// Shaders #include this to get the light bindings, which are generated since
+// they're declared differently depending on where the lights are stored. The
+// bind group is whatever LIGHTS_GROUP is #defined as.
+pub const LIGHTS_INCLUDE: &str = "lights.wgsl";
+
+// Must match LightRaw and LightCountUniform
+const LIGHT_STRUCTS: &str = "const LIGHT_POINT: u32 = 0u;
+const LIGHT_SPOT: u32 = 1u;
+const LIGHT_DIRECTIONAL: u32 = 2u;
+
+struct Light {
+    position: vec3<f32>,
+    kind: u32,
+    direction: vec3<f32>,
+    range: f32,
+    color: vec3<f32>,
+    intensity: f32,
+    inner_cone_cos: f32,
+    outer_cone_cos: f32,
+}
+
+struct LightCount {
+    count: u32,
+    _padding0: u32,
+    _padding1: u32,
+    _padding2: u32,
+}
+";
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct LightCountUniform {
+    count: u32,
+    // Uniform buffers have to be at least 16 bytes
+    _padding: [u32; 3],
+}
+
+// Holds every light in the scene in one buffer, along with how many there are.
+// Lights go in a storage buffer that can grow as needed, except on WebGL2
+// (downlevel_webgl2_defaults has no storage buffers) where they go in a fixed
+// size uniform array instead.
+pub struct LightManager {
+    lights: Vec<Light>,
+    use_storage_buffer: bool,
+    capacity: usize,
+    light_buffer: wgpu::Buffer,
+    count_buffer: wgpu::Buffer,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+}
+
+impl LightManager {
+    // `vertex_storage` is whether the adapter supports storage buffers in
+    // vertex shaders (DownlevelFlags::VERTEX_STORAGE), as the light gizmos read
+    // the lights from there.
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        lights: &[Light],
+        vertex_storage: bool,
+    ) -> Self {
+        let use_storage_buffer =
+            vertex_storage && device.limits().max_storage_buffers_per_shader_stage > 0;
+        let capacity = if use_storage_buffer {
+            lights.len().max(1)
+        } else {
+            MAX_UNIFORM_LIGHTS
+        };
+
+        let count_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Light Count Buffer"),
+            contents: bytemuck::cast_slice(&[LightCountUniform {
+                count: 0,
+                _padding: [0; 3],
+            }]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let light_binding = if use_storage_buffer {
+            wgpu::BufferBindingType::Storage { read_only: true }
+        } else {
+            wgpu::BufferBindingType::Uniform
+        };
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: light_binding,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("light_bind_group_layout"),
+        });
+
+        let light_buffer = Self::create_light_buffer(device, capacity, use_storage_buffer);
+        let bind_group =
+            Self::create_bind_group(device, &bind_group_layout, &light_buffer, &count_buffer);
+
+        let mut manager = Self {
+            lights: Vec::new(),
+            use_storage_buffer,
+            capacity,
+            light_buffer,
+            count_buffer,
+            bind_group_layout,
+            bind_group,
+        };
+        manager.set_lights(device, queue, lights);
+        manager
+    }
+
+    fn create_light_buffer(
+        device: &wgpu::Device,
+        capacity: usize,
+        use_storage_buffer: bool,
+    ) -> wgpu::Buffer {
+        let usage = if use_storage_buffer {
+            wgpu::BufferUsages::STORAGE
+        } else {
+            wgpu::BufferUsages::UNIFORM
+        };
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Light Buffer"),
+            size: (capacity * std::mem::size_of::<LightRaw>()) as wgpu::BufferAddress,
+            usage: usage | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        light_buffer: &wgpu::Buffer,
+        count_buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: light_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: count_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("light_bind_group"),
+        })
+    }
+
+    pub fn set_lights(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, lights: &[Light]) {
+        let mut lights = lights.to_vec();
+        if !self.use_storage_buffer && lights.len() > MAX_UNIFORM_LIGHTS {
+            log::warn!(
+                "Only the first {MAX_UNIFORM_LIGHTS} of {} lights are used without storage buffers",
+                lights.len()
+            );
+            lights.truncate(MAX_UNIFORM_LIGHTS);
+        }
+
+        // Only storage buffers can ever run out of room, the uniform array is always full size
+        if lights.len() > self.capacity {
+            self.capacity = lights.len().next_power_of_two();
+            self.light_buffer = Self::create_light_buffer(device, self.capacity, true);
+            self.bind_group = Self::create_bind_group(
+                device,
+                &self.bind_group_layout,
+                &self.light_buffer,
+                &self.count_buffer,
+            );
+        }
+
+        let raw = lights.iter().map(Light::to_raw).collect::<Vec<_>>();
+        queue.write_buffer(&self.light_buffer, 0, bytemuck::cast_slice(&raw));
+        queue.write_buffer(
+            &self.count_buffer,
+            0,
+            bytemuck::cast_slice(&[LightCountUniform {
+                count: raw.len() as u32,
+                _padding: [0; 3],
+            }]),
+        );
+
+        self.lights = lights;
+    }
+
+    pub fn lights(&self) -> &[Light] {
+        &self.lights
+    }
+
+    pub fn uses_storage_buffer(&self) -> bool {
+        self.use_storage_buffer
+    }
+
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    // The contents of LIGHTS_INCLUDE for this manager's kind of buffer
+    pub fn include_source(&self) -> String {
+        Self::bindings_source(self.use_storage_buffer)
+    }
+
+    // The Light struct and the lights/light_count bindings, for either kind of buffer
+    pub fn bindings_source(use_storage_buffer: bool) -> String {
+        let lights = if use_storage_buffer {
+            "@group(LIGHTS_GROUP) @binding(0)\nvar<storage, read> lights: array<Light>;".to_string()
+        } else {
+            format!(
+                "@group(LIGHTS_GROUP) @binding(0)\nvar<uniform> lights: array<Light, {MAX_UNIFORM_LIGHTS}>;"
+            )
+        };
+        let count = "@group(LIGHTS_GROUP) @binding(1)\nvar<uniform> light_count: LightCount;";
+
+        format!("{LIGHT_STRUCTS}\n{lights}\n{count}\n")
+    }
+}
@@ -0,0 +1,3 @@
+pub mod light;
+pub mod light_manager;
+pub mod shadow_map;
@@ -0,0 +1,326 @@
+use cgmath::{EuclideanSpace, InnerSpace};
+
+use crate::types::camera_types::camera::OPENGL_TO_WGPU_MATRIX;
+
+use super::light::{Light, LightKind};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ShadowSettings {
+    // Width and height of the shadow map in texels
+    pub resolution: u32,
+    // Added to every depth written to the shadow map, in the smallest steps the
+    // depth format can represent. Stops surfaces from shadowing themselves ("acne").
+    pub depth_bias: i32,
+    // Extra bias for surfaces that are steep as seen from the light
+    pub slope_bias: f32,
+    // Half the width of the square area around `center` that casts and receives shadows
+    pub extent: f32,
+    pub center: cgmath::Point3<f32>,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        Self {
+            resolution: 1024,
+            depth_bias: 2,
+            slope_bias: 2.0,
+            extent: 4.0,
+            center: cgmath::Point3::origin(),
+        }
+    }
+}
+
+// Matches ShadowUniform in lighting.wgsl and shadow_shader.wgsl
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct ShadowUniform {
+    light_view_proj: [[f32; 4]; 4],
+    // Index of the light casting the shadow, or -1 if none does
+    light_index: i32,
+    // Size of one shadow map texel in uv units, for PCF
+    texel_size: f32,
+    _padding: [u32; 2],
+}
+
+// A depth texture rendered from the first directional light's point of view.
+// Anything further from the light than what's stored in the map is in shadow.
+pub struct ShadowMap {
+    settings: ShadowSettings,
+    // Whether a light is casting a shadow at all, the shadow pass is skipped otherwise
+    active: bool,
+    #[allow(unused)]
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    sampler: wgpu::Sampler,
+    uniform_buffer: wgpu::Buffer,
+    // For the shadow pass, just the uniform
+    pass_bind_group_layout: wgpu::BindGroupLayout,
+    pass_bind_group: wgpu::BindGroup,
+    // For the lit pass: the map, its comparison sampler and the uniform
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+}
+
+impl ShadowMap {
+    pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        settings: ShadowSettings,
+        lights: &[Light],
+    ) -> Self {
+        use wgpu::util::DeviceExt;
+
+        let (texture, view) = Self::create_texture(device, settings.resolution);
+
+        // A comparison sampler returns how much of the sampled texel passes the
+        // compare against the given depth, instead of the depth itself
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shadow Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Shadow Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[Self::uniform(&settings, None)]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let uniform_entry = |binding, visibility| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+
+        let pass_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[uniform_entry(0, wgpu::ShaderStages::VERTEX)],
+                label: Some("shadow_pass_bind_group_layout"),
+            });
+        let pass_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &pass_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+            label: Some("shadow_pass_bind_group"),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Depth,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                    count: None,
+                },
+                uniform_entry(2, wgpu::ShaderStages::FRAGMENT),
+            ],
+            label: Some("shadow_bind_group_layout"),
+        });
+        let bind_group =
+            Self::create_bind_group(device, &bind_group_layout, &view, &sampler, &uniform_buffer);
+
+        let mut shadow_map = Self {
+            settings,
+            active: false,
+            texture,
+            view,
+            sampler,
+            uniform_buffer,
+            pass_bind_group_layout,
+            pass_bind_group,
+            bind_group_layout,
+            bind_group,
+        };
+        shadow_map.update(queue, lights);
+        shadow_map
+    }
+
+    fn create_texture(
+        device: &wgpu::Device,
+        resolution: u32,
+    ) -> (wgpu::Texture, wgpu::TextureView) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Shadow Map"),
+            size: wgpu::Extent3d {
+                width: resolution.max(1),
+                height: resolution.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        (texture, view)
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        view: &wgpu::TextureView,
+        sampler: &wgpu::Sampler,
+        uniform_buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("shadow_bind_group"),
+        })
+    }
+
+    fn uniform(settings: &ShadowSettings, light: Option<(usize, &Light)>) -> ShadowUniform {
+        use cgmath::SquareMatrix;
+
+        let (light_index, light_view_proj) = match light {
+            Some((index, light)) => (index as i32, Self::light_view_proj(settings, light)),
+            None => (-1, cgmath::Matrix4::identity()),
+        };
+
+        ShadowUniform {
+            light_view_proj: light_view_proj.into(),
+            light_index,
+            texel_size: 1.0 / settings.resolution.max(1) as f32,
+            _padding: [0; 2],
+        }
+    }
+
+    // An orthographic view looking down the light's direction at `center`, big
+    // enough to hold a sphere of radius `extent` around it.
+    fn light_view_proj(settings: &ShadowSettings, light: &Light) -> cgmath::Matrix4<f32> {
+        let direction = light.direction.normalize();
+        // look_at_rh can't handle looking straight along `up`
+        let up = if direction.y.abs() > 0.99 {
+            cgmath::Vector3::unit_z()
+        } else {
+            cgmath::Vector3::unit_y()
+        };
+
+        let extent = settings.extent;
+        let eye = settings.center - direction * extent * 2.0;
+        let view = cgmath::Matrix4::look_at_rh(eye, settings.center, up);
+        let proj = cgmath::ortho(-extent, extent, -extent, extent, 0.0, extent * 4.0);
+
+        OPENGL_TO_WGPU_MATRIX * proj * view
+    }
+
+    // Picks the light that casts the shadow and points the shadow map at it.
+    // Needs calling whenever the lights change.
+    pub fn update(&mut self, queue: &wgpu::Queue, lights: &[Light]) {
+        let light = lights
+            .iter()
+            .enumerate()
+            .find(|(_, light)| light.kind == LightKind::Directional);
+        self.active = light.is_some();
+        queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[Self::uniform(&self.settings, light)]),
+        );
+    }
+
+    // A new resolution means a new texture, the bias is baked into the shadow
+    // pipeline so that needs rebuilding by the caller.
+    pub fn set_settings(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        settings: ShadowSettings,
+        lights: &[Light],
+    ) {
+        if settings.resolution != self.settings.resolution {
+            (self.texture, self.view) = Self::create_texture(device, settings.resolution);
+            self.bind_group = Self::create_bind_group(
+                device,
+                &self.bind_group_layout,
+                &self.view,
+                &self.sampler,
+                &self.uniform_buffer,
+            );
+        }
+        self.settings = settings;
+        self.update(queue, lights);
+    }
+
+    pub fn settings(&self) -> &ShadowSettings {
+        &self.settings
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    pub fn depth_stencil_state(&self) -> wgpu::DepthStencilState {
+        wgpu::DepthStencilState {
+            format: Self::DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState {
+                constant: self.settings.depth_bias,
+                slope_scale: self.settings.slope_bias,
+                clamp: 0.0,
+            },
+        }
+    }
+
+    pub fn pass_bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.pass_bind_group_layout
+    }
+
+    pub fn pass_bind_group(&self) -> &wgpu::BindGroup {
+        &self.pass_bind_group
+    }
+
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+}
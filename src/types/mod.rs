@@ -1,5 +1,22 @@
+pub mod camera_types;
+pub mod cli;
+pub mod culling;
+mod equirect;
+pub mod examples;
+mod frame_capture;
+pub mod gltf_scene;
+mod instance_buffer;
+pub mod light_types;
+mod mipmaps;
+pub mod model;
+pub mod pipeline_builder;
+pub mod polygon_buffer;
+pub mod preprocessor;
+pub mod reflection;
+mod render_target;
+pub mod shader_library;
+pub mod skybox;
 pub mod state;
-mod polygon_buffer;
-mod vertex_types;
-mod camera_types;
-mod texture;
\ No newline at end of file
+pub mod texture;
+pub mod timing;
+pub mod vertex_types;
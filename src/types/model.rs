@@ -0,0 +1,338 @@
+use std::{io::BufReader, ops::Range, path::Path};
+
+use anyhow::*;
+
+use super::{polygon_buffer::PolygonBuffer, texture, vertex_types::model_vertex::ModelVertex};
+
+// A mesh as it comes out of the file, before anything is uploaded to the GPU.
+pub struct MeshData {
+    pub name: String,
+    pub vertices: Vec<ModelVertex>,
+    pub indices: Vec<u32>,
+    pub material: Option<usize>,
+}
+
+pub struct MaterialData {
+    pub name: String,
+    // Path of the diffuse texture, relative to the material file
+    pub diffuse_texture: Option<String>,
+}
+
+pub struct Mesh {
+    pub name: String,
+    pub polygon_buffer: PolygonBuffer<ModelVertex>,
+    pub material: Option<usize>,
+}
+
+pub struct Material {
+    pub name: String,
+    pub diffuse_texture: texture::Texture,
+    pub bind_group: wgpu::BindGroup,
+}
+
+pub struct Model {
+    pub meshes: Vec<Mesh>,
+    pub materials: Vec<Material>,
+    // Meshes without a material of their own are drawn with this plain white one.
+    default_material: Material,
+}
+
+// Parses an OBJ file and the MTL files it references. Faces are triangulated
+// and every unique position/uv/normal combination becomes a single vertex.
+// `load_file` is handed the paths of referenced files exactly as written in the OBJ.
+pub fn parse_obj(
+    obj: &[u8],
+    load_file: impl Fn(&Path) -> Result<Vec<u8>>,
+) -> Result<(Vec<MeshData>, Vec<MaterialData>)> {
+    let (models, materials) = tobj::load_obj_buf(
+        &mut BufReader::new(obj),
+        &tobj::LoadOptions {
+            triangulate: true,
+            single_index: true,
+            ..Default::default()
+        },
+        |path| {
+            let bytes = load_file(path).map_err(|e| {
+                log::error!("Couldn't read material file {}: {e:#}", path.display());
+                tobj::LoadError::OpenFileFailed
+            })?;
+            tobj::load_mtl_buf(&mut BufReader::new(bytes.as_slice()))
+        },
+    )
+    .context("malformed OBJ file")?;
+    let materials = materials.context("malformed MTL file")?;
+
+    let meshes = models
+        .into_iter()
+        .map(|model| {
+            let mesh = model.mesh;
+            let num_vertices = mesh.positions.len() / 3;
+
+            if mesh.indices.len() % 3 != 0 {
+                bail!(
+                    "mesh {} has {} indices, which can't be split into triangles",
+                    model.name,
+                    mesh.indices.len()
+                );
+            }
+            if let Some(index) = mesh.indices.iter().find(|i| **i as usize >= num_vertices) {
+                bail!(
+                    "mesh {} references vertex {index}, but only has {num_vertices}",
+                    model.name
+                );
+            }
+            if let Some(id) = mesh.material_id.filter(|id| *id >= materials.len()) {
+                bail!(
+                    "mesh {} uses material {id}, but only {} were loaded",
+                    model.name,
+                    materials.len()
+                );
+            }
+
+            let mut vertices = (0..num_vertices)
+                .map(|i| ModelVertex {
+                    position: [
+                        mesh.positions[i * 3],
+                        mesh.positions[i * 3 + 1],
+                        mesh.positions[i * 3 + 2],
+                    ],
+                    // OBJ has v pointing up, wgpu has it pointing down
+                    tex_coords: if mesh.texcoords.is_empty() {
+                        [0.0, 0.0]
+                    } else {
+                        [mesh.texcoords[i * 2], 1.0 - mesh.texcoords[i * 2 + 1]]
+                    },
+                    normal: if mesh.normals.is_empty() {
+                        [0.0, 0.0, 0.0]
+                    } else {
+                        [
+                            mesh.normals[i * 3],
+                            mesh.normals[i * 3 + 1],
+                            mesh.normals[i * 3 + 2],
+                        ]
+                    },
+                })
+                .collect::<Vec<_>>();
+
+            if mesh.normals.is_empty() {
+                let positions = vertices.iter().map(|v| v.position).collect::<Vec<_>>();
+                for (vertex, normal) in vertices
+                    .iter_mut()
+                    .zip(compute_normals(&positions, &mesh.indices))
+                {
+                    vertex.normal = normal;
+                }
+            }
+
+            Ok(MeshData {
+                name: model.name,
+                vertices,
+                indices: mesh.indices,
+                material: mesh.material_id,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let materials = materials
+        .into_iter()
+        .map(|m| MaterialData {
+            name: m.name,
+            diffuse_texture: m.diffuse_texture,
+        })
+        .collect();
+
+    Ok((meshes, materials))
+}
+
+// Smooth normals for files that don't have any: every vertex gets the average
+// of the faces around it, weighted by their area.
+pub(crate) fn compute_normals(positions: &[[f32; 3]], indices: &[u32]) -> Vec<[f32; 3]> {
+    use cgmath::{InnerSpace, Vector3, Zero};
+
+    let mut normals = vec![Vector3::<f32>::zero(); positions.len()];
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] =
+            [triangle[0], triangle[1], triangle[2]].map(|i| Vector3::from(positions[i as usize]));
+        let face_normal = (b - a).cross(c - a);
+        for i in triangle {
+            normals[*i as usize] += face_normal;
+        }
+    }
+
+    normals
+        .into_iter()
+        .map(|n| {
+            if n.magnitude2() > 0.0 {
+                n.normalize().into()
+            } else {
+                [0.0, 0.0, 0.0]
+            }
+        })
+        .collect()
+}
+
+impl Model {
+    // Loads an OBJ file from disk; materials and textures are looked up next to it.
+    pub fn load(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        path: impl AsRef<Path>,
+        layout: &wgpu::BindGroupLayout,
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        let dir = path.parent().unwrap_or(Path::new(""));
+        let obj =
+            std::fs::read(path).with_context(|| format!("couldn't read {}", path.display()))?;
+
+        Self::from_obj_bytes(
+            device,
+            queue,
+            &obj,
+            |file| {
+                let file = dir.join(file);
+                std::fs::read(&file).with_context(|| format!("couldn't read {}", file.display()))
+            },
+            layout,
+        )
+    }
+
+    pub fn from_obj_bytes(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        obj: &[u8],
+        load_file: impl Fn(&Path) -> Result<Vec<u8>>,
+        layout: &wgpu::BindGroupLayout,
+    ) -> Result<Self> {
+        let (mesh_data, material_data) = parse_obj(obj, &load_file)?;
+
+        let materials = material_data
+            .into_iter()
+            .map(|m| {
+                let diffuse_texture = match &m.diffuse_texture {
+                    Some(file) => {
+                        let bytes = load_file(Path::new(file))?;
+                        texture::Texture::from_bytes(
+                            device,
+                            queue,
+                            &bytes,
+                            file,
+                            texture::TextureOptions::TRILINEAR,
+                        )
+                        .with_context(|| {
+                            format!("couldn't load texture {file} of material {}", m.name)
+                        })?
+                    }
+                    None => white_texture(device, queue)?,
+                };
+                Ok(Material::new(device, m.name, diffuse_texture, layout))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let meshes = mesh_data
+            .into_iter()
+            .map(|m| Mesh {
+                polygon_buffer: PolygonBuffer::with_u32_indices(device, &m.vertices, &m.indices),
+                name: m.name,
+                material: m.material,
+            })
+            .collect();
+
+        let default_material = Material::new(
+            device,
+            "default".to_string(),
+            white_texture(device, queue)?,
+            layout,
+        );
+
+        Ok(Self {
+            meshes,
+            materials,
+            default_material,
+        })
+    }
+
+    pub fn material_for(&self, mesh: &Mesh) -> &Material {
+        mesh.material
+            .map_or(&self.default_material, |id| &self.materials[id])
+    }
+}
+
+impl Material {
+    pub fn new(
+        device: &wgpu::Device,
+        name: String,
+        diffuse_texture: texture::Texture,
+        layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&diffuse_texture.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&diffuse_texture.sampler),
+                },
+            ],
+            label: Some(&name),
+        });
+
+        Self {
+            name,
+            diffuse_texture,
+            bind_group,
+        }
+    }
+}
+
+pub(crate) fn white_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+) -> Result<texture::Texture> {
+    let img =
+        image::DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(1, 1, image::Rgba([255; 4])));
+    texture::Texture::from_image(
+        device,
+        queue,
+        &img,
+        Some("white"),
+        texture::TextureOptions::default(),
+    )
+}
+
+// Draws a model into a render pass whose pipeline has the material's texture
+// at group 0. Anything else the pipeline needs (camera etc.) must already be bound.
+pub trait DrawModel<'a> {
+    fn draw_mesh_instanced(
+        &mut self,
+        mesh: &'a Mesh,
+        material: &'a Material,
+        instances: Range<u32>,
+    );
+    fn draw_model_instanced(&mut self, model: &'a Model, instances: Range<u32>);
+}
+
+impl<'a> DrawModel<'a> for wgpu::RenderPass<'a> {
+    fn draw_mesh_instanced(
+        &mut self,
+        mesh: &'a Mesh,
+        material: &'a Material,
+        instances: Range<u32>,
+    ) {
+        self.set_bind_group(0, &material.bind_group, &[]);
+        self.set_vertex_buffer(0, mesh.polygon_buffer.vertex_buffer.slice(..));
+        self.set_index_buffer(
+            mesh.polygon_buffer.index_buffer.slice(..),
+            mesh.polygon_buffer.index_format,
+        );
+        self.draw_indexed(0..mesh.polygon_buffer.num_indices, 0, instances);
+    }
+
+    fn draw_model_instanced(&mut self, model: &'a Model, instances: Range<u32>) {
+        for mesh in &model.meshes {
+            self.draw_mesh_instanced(mesh, model.material_for(mesh), instances.clone());
+        }
+    }
+}
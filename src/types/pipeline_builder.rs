@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result, bail, ensure};
+
+use super::reflection::ShaderReflection;
+
+// Builds a render pipeline from WGSL source. It starts out as what most of our
+// pipelines use: vs_main and fs_main, a triangle list with back faces culled,
+// and no multisampling. Color targets, depth and everything else are added with
+// the setters. build checks them against the device and the shader's inputs and
+// outputs before wgpu sees them, as far as that can be done without the adapter.
+pub struct PipelineBuilder<'a> {
+    label: &'a str,
+    source: &'a str,
+    layout: Option<&'a wgpu::PipelineLayout>,
+    vertex_entry_point: &'a str,
+    // None for depth only pipelines
+    fragment_entry_point: Option<&'a str>,
+    buffers: Vec<wgpu::VertexBufferLayout<'a>>,
+    color_targets: Vec<Option<wgpu::ColorTargetState>>,
+    primitive: wgpu::PrimitiveState,
+    depth_stencil: Option<wgpu::DepthStencilState>,
+    multisample: wgpu::MultisampleState,
+    // Values for the shader's `override` declarations, by name or @id
+    constants: HashMap<String, f64>,
+}
+
+impl<'a> PipelineBuilder<'a> {
+    // `label` names the pipeline and its shader module, and prefixes build's errors
+    pub fn new(label: &'a str, source: &'a str) -> Self {
+        Self {
+            label,
+            source,
+            layout: None,
+            vertex_entry_point: "vs_main",
+            fragment_entry_point: Some("fs_main"),
+            buffers: Vec::new(),
+            color_targets: Vec::new(),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList, // every three vertices make a triangle
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw, // triangles whose vertices go counter-clockwise face forwards
+                cull_mode: Some(wgpu::Face::Back), // so the ones facing away aren't drawn
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            constants: HashMap::new(),
+        }
+    }
+
+    // Without one, wgpu works the layout out from the shader
+    pub fn layout(mut self, layout: &'a wgpu::PipelineLayout) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+
+    pub fn vertex_entry_point(mut self, name: &'a str) -> Self {
+        self.vertex_entry_point = name;
+        self
+    }
+
+    pub fn fragment_entry_point(mut self, name: &'a str) -> Self {
+        self.fragment_entry_point = Some(name);
+        self
+    }
+
+    // Only runs the vertex stage, for pipelines that just write depth
+    pub fn no_fragment(mut self) -> Self {
+        self.fragment_entry_point = None;
+        self
+    }
+
+    // Buffers are bound in the order they're added
+    pub fn vertex_buffer(mut self, layout: wgpu::VertexBufferLayout<'a>) -> Self {
+        self.buffers.push(layout);
+        self
+    }
+
+    pub fn vertex_buffers(
+        mut self,
+        layouts: impl IntoIterator<Item = wgpu::VertexBufferLayout<'a>>,
+    ) -> Self {
+        self.buffers.extend(layouts);
+        self
+    }
+
+    // A target that's simply overwritten, the next @location(n) after any already
+    // added. There's no blending, so it works with formats that can't be blended.
+    pub fn color_target(self, format: wgpu::TextureFormat) -> Self {
+        self.color_target_state(wgpu::ColorTargetState {
+            format,
+            blend: None,
+            write_mask: wgpu::ColorWrites::ALL,
+        })
+    }
+
+    // Blending needs a format that can be blended, which leaves out integer
+    // formats and (without Features::FLOAT32_BLENDABLE) 32 bit floats
+    pub fn color_target_state(mut self, state: wgpu::ColorTargetState) -> Self {
+        self.color_targets.push(Some(state));
+        self
+    }
+
+    pub fn topology(mut self, topology: wgpu::PrimitiveTopology) -> Self {
+        self.primitive.topology = topology;
+        self
+    }
+
+    // Needed by strip topologies to recognize primitive restart indices
+    pub fn strip_index_format(mut self, format: wgpu::IndexFormat) -> Self {
+        self.primitive.strip_index_format = Some(format);
+        self
+    }
+
+    pub fn front_face(mut self, front_face: wgpu::FrontFace) -> Self {
+        self.primitive.front_face = front_face;
+        self
+    }
+
+    // None draws both sides
+    pub fn cull_mode(mut self, cull_mode: Option<wgpu::Face>) -> Self {
+        self.primitive.cull_mode = cull_mode;
+        self
+    }
+
+    // Line needs Features::POLYGON_MODE_LINE (what used to be NON_FILL_POLYGON_MODE),
+    // Point needs POLYGON_MODE_POINT
+    pub fn polygon_mode(mut self, polygon_mode: wgpu::PolygonMode) -> Self {
+        self.primitive.polygon_mode = polygon_mode;
+        self
+    }
+
+    // Needs Features::DEPTH_CLIP_CONTROL
+    pub fn unclipped_depth(mut self, unclipped_depth: bool) -> Self {
+        self.primitive.unclipped_depth = unclipped_depth;
+        self
+    }
+
+    // Needs Features::CONSERVATIVE_RASTERIZATION
+    pub fn conservative(mut self, conservative: bool) -> Self {
+        self.primitive.conservative = conservative;
+        self
+    }
+
+    pub fn depth_stencil(mut self, state: wgpu::DepthStencilState) -> Self {
+        self.depth_stencil = Some(state);
+        self
+    }
+
+    // Every target, and the depth buffer, has to have this many samples
+    pub fn sample_count(mut self, count: u32) -> Self {
+        self.multisample.count = count;
+        self
+    }
+
+    pub fn alpha_to_coverage(mut self, enabled: bool) -> Self {
+        self.multisample.alpha_to_coverage_enabled = enabled;
+        self
+    }
+
+    // Sets an `override` in the shader, by its name or @id
+    pub fn constant(mut self, key: impl Into<String>, value: f64) -> Self {
+        self.constants.insert(key.into(), value);
+        self
+    }
+
+    pub fn build(&self, device: &wgpu::Device) -> Result<wgpu::RenderPipeline> {
+        self.validate(device)
+            .with_context(|| format!("couldn't build the {} pipeline", self.label))?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(self.label),
+            source: wgpu::ShaderSource::Wgsl(self.source.into()),
+        });
+        let compilation_options = wgpu::PipelineCompilationOptions {
+            constants: &self.constants,
+            ..Default::default()
+        };
+
+        Ok(
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(self.label),
+                layout: self.layout,
+                vertex: wgpu::VertexState {
+                    module: &module,
+                    entry_point: Some(self.vertex_entry_point),
+                    buffers: &self.buffers,
+                    compilation_options: compilation_options.clone(),
+                },
+                fragment: self
+                    .fragment_entry_point
+                    .map(|entry_point| wgpu::FragmentState {
+                        module: &module,
+                        entry_point: Some(entry_point),
+                        targets: &self.color_targets,
+                        compilation_options,
+                    }),
+                primitive: self.primitive,
+                depth_stencil: self.depth_stencil.clone(),
+                multisample: self.multisample,
+                multiview: None,
+                cache: None,
+            }),
+        )
+    }
+
+    // Everything wgpu would otherwise panic over, or that's easy to get wrong
+    fn validate(&self, device: &wgpu::Device) -> Result<()> {
+        let features = device.features();
+        let needs = |feature: wgpu::Features, what: &str| -> Result<()> {
+            let name = feature
+                .iter_names()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>()
+                .join(" | ");
+            ensure!(
+                features.contains(feature),
+                "{what} needs Features::{name}, which the device doesn't have"
+            );
+            Ok(())
+        };
+        match self.primitive.polygon_mode {
+            wgpu::PolygonMode::Fill => {}
+            wgpu::PolygonMode::Line => {
+                needs(wgpu::Features::POLYGON_MODE_LINE, "PolygonMode::Line")?
+            }
+            wgpu::PolygonMode::Point => {
+                needs(wgpu::Features::POLYGON_MODE_POINT, "PolygonMode::Point")?
+            }
+        }
+        if self.primitive.unclipped_depth {
+            needs(wgpu::Features::DEPTH_CLIP_CONTROL, "unclipped depth")?;
+        }
+        if self.primitive.conservative {
+            needs(
+                wgpu::Features::CONSERVATIVE_RASTERIZATION,
+                "conservative rasterization",
+            )?;
+            ensure!(
+                self.primitive.polygon_mode == wgpu::PolygonMode::Fill,
+                "conservative rasterization only works with PolygonMode::Fill"
+            );
+        }
+        if self.primitive.strip_index_format.is_some() {
+            ensure!(
+                self.primitive.topology.is_strip(),
+                "a strip index format only makes sense with a strip topology"
+            );
+        }
+
+        ensure!(
+            !self.color_targets.is_empty() || self.depth_stencil.is_some(),
+            "there are no color targets or depth buffer to draw to"
+        );
+        if self.fragment_entry_point.is_none() {
+            ensure!(
+                self.color_targets.is_empty(),
+                "color targets need a fragment shader to write them"
+            );
+        }
+
+        // Any count other than 1 and 4 depends on the adapter, which isn't known here
+        let count = self.multisample.count;
+        ensure!(
+            count.is_power_of_two(),
+            "a sample count of {count} isn't a power of two"
+        );
+        if !features.contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES) {
+            let formats = self
+                .color_targets
+                .iter()
+                .flatten()
+                .map(|target| target.format);
+            for format in formats.chain(self.depth_stencil.as_ref().map(|depth| depth.format)) {
+                let supported = format
+                    .guaranteed_format_features(features)
+                    .flags
+                    .sample_count_supported(count);
+                ensure!(supported, "{format:?} doesn't support {count} samples");
+            }
+        }
+        // Same as the sample count, the adapter may be able to blend more formats
+        if !features.contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES) {
+            for target in self
+                .color_targets
+                .iter()
+                .flatten()
+                .filter(|target| target.blend.is_some())
+            {
+                let format = target.format;
+                let blendable = format
+                    .guaranteed_format_features(features)
+                    .flags
+                    .contains(wgpu::TextureFormatFeatureFlags::BLENDABLE);
+                ensure!(
+                    blendable,
+                    "{format:?} can't be blended, leave the color target's blend as None"
+                );
+            }
+        }
+        if self.multisample.alpha_to_coverage_enabled {
+            ensure!(count > 1, "alpha to coverage needs multisampling");
+        }
+
+        let shader = ShaderReflection::new(self.source)?;
+        shader.check_vertex_inputs(self.vertex_entry_point, &self.buffers)?;
+        if let Some(entry_point) = self.fragment_entry_point {
+            shader.check_fragment_outputs(entry_point, &self.color_targets)?;
+        }
+        if let Some(key) = self.constants.keys().find(|key| !shader.has_override(key)) {
+            bail!("there's no override called {key} for the constant to set");
+        }
+        Ok(())
+    }
+}
@@ -1,40 +1,84 @@
 use std::marker::PhantomData;
 
-use wgpu::{util::DeviceExt, Device};
+use wgpu::{Device, util::DeviceExt};
 
-use super::vertex_types::Vertex;
+use super::{
+    culling::{Aabb, BoundingSphere},
+    vertex_types::MeshVertex,
+};
 
-pub struct PolygonBuffer<T: bytemuck::Pod + bytemuck::Zeroable + Vertex> {
+pub struct PolygonBuffer<T: bytemuck::Pod + bytemuck::Zeroable + MeshVertex> {
     // check macro kata to make stuff like this more readable
     pub vertex_buffer: wgpu::Buffer,
     pub index_buffer: wgpu::Buffer,
     pub _num_vertices: u32,
     pub num_indices: u32,
+    pub index_format: wgpu::IndexFormat,
+    // Worked out from the vertices when the buffer is made, for frustum culling
+    pub aabb: Aabb,
+    pub bounding_sphere: BoundingSphere,
     _marker: PhantomData<T>,
 }
 
-impl<T: bytemuck::Pod + bytemuck::Zeroable + Vertex> PolygonBuffer<T> {
+impl<T: bytemuck::Pod + bytemuck::Zeroable + MeshVertex> PolygonBuffer<T> {
     pub fn new(device: &Device, vertices: &[T], indices: &[u16]) -> Self {
-        let vertex_buffer = device.create_buffer_init(
-            &wgpu::util::BufferInitDescriptor {
-                label: Some("Vertex Buffer"),
-                contents: bytemuck::cast_slice(vertices),
-                usage: wgpu::BufferUsages::VERTEX,
-            }
-        );
-
-        let index_buffer = device.create_buffer_init(
-            &wgpu::util::BufferInitDescriptor {
-                label: Some("Index Buffer"),
-                contents: bytemuck::cast_slice(indices),
-                usage: wgpu::BufferUsages::INDEX,
-            }
-        );
-        
+        Self::from_index_bytes(
+            device,
+            vertices,
+            bytemuck::cast_slice(indices),
+            indices.len() as u32,
+            wgpu::IndexFormat::Uint16,
+        )
+    }
+
+    // Meshes loaded from files can easily have more than u16::MAX vertices
+    pub fn with_u32_indices(device: &Device, vertices: &[T], indices: &[u32]) -> Self {
+        Self::from_index_bytes(
+            device,
+            vertices,
+            bytemuck::cast_slice(indices),
+            indices.len() as u32,
+            wgpu::IndexFormat::Uint32,
+        )
+    }
+
+    fn from_index_bytes(
+        device: &Device,
+        vertices: &[T],
+        indices: &[u8],
+        num_indices: u32,
+        index_format: wgpu::IndexFormat,
+    ) -> Self {
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Vertex Buffer"),
+            contents: bytemuck::cast_slice(vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Index Buffer"),
+            contents: indices,
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
         let _num_vertices = vertices.len() as u32;
 
-        let num_indices = indices.len() as u32;
+        let positions = vertices
+            .iter()
+            .map(MeshVertex::position)
+            .collect::<Vec<_>>();
+        let aabb = Aabb::from_points(positions.iter().copied());
+        let bounding_sphere = BoundingSphere::from_points(&positions);
 
-        Self { vertex_buffer, index_buffer, _num_vertices, num_indices, _marker: PhantomData }
+        Self {
+            vertex_buffer,
+            index_buffer,
+            _num_vertices,
+            num_indices,
+            index_format,
+            aabb,
+            bounding_sphere,
+            _marker: PhantomData,
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,250 @@
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
+
+use anyhow::{Context, Result, anyhow, bail};
+
+use super::reflection::ShaderReflection;
+
+// Looks up a file by the name it's included as
+type Loader<'a> = Box<dyn Fn(&str) -> Result<Cow<'static, str>> + 'a>;
+
+// Resolves the directives shaders can use on top of plain WGSL, each on a line of
+// its own:
+//
+//   #include "file.wgsl"   pastes in another file, once per shader however often
+//                          it's included
+//   #define NAME [value]   defines NAME, and replaces it with `value` (if there is
+//                          one) wherever it appears as a whole word afterwards
+//   #undef NAME
+//   #ifdef NAME / #ifndef NAME / #else / #endif
+//                          keeps or drops the lines between them
+pub struct Preprocessor<'a> {
+    load: Loader<'a>,
+    defines: HashMap<String, String>,
+}
+
+// The source that's handed to wgpu, plus where each of its lines came from
+#[derive(Clone, Debug, Default)]
+pub struct ProcessedShader {
+    pub source: String,
+    // Every file that went into the shader, the one that was processed first
+    files: Vec<String>,
+    // (index into files, line number) for each line of source
+    lines: Vec<(usize, u32)>,
+}
+
+// One #ifdef or #ifndef that hasn't reached its #endif yet
+struct Condition {
+    keep: bool,
+    seen_else: bool,
+    line: u32,
+}
+
+struct Processing {
+    defines: HashMap<String, String>,
+    // The chain of files currently being included, to catch cycles
+    stack: Vec<String>,
+    included: HashSet<String>,
+    shader: ProcessedShader,
+}
+
+impl<'a> Preprocessor<'a> {
+    pub fn new(load: impl Fn(&str) -> Result<Cow<'static, str>> + 'a) -> Self {
+        Self {
+            load: Box::new(load),
+            defines: HashMap::new(),
+        }
+    }
+
+    // Same as a #define at the top of the shader. An empty value only switches
+    // #ifdef blocks on.
+    pub fn define(&mut self, name: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.defines.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn process(&self, name: &str) -> Result<ProcessedShader> {
+        let mut processing = Processing {
+            defines: self.defines.clone(),
+            stack: Vec::new(),
+            included: HashSet::new(),
+            shader: ProcessedShader::default(),
+        };
+        self.process_file(name, &mut processing)?;
+        Ok(processing.shader)
+    }
+
+    fn process_file(&self, name: &str, processing: &mut Processing) -> Result<()> {
+        if processing.stack.iter().any(|file| file == name) {
+            bail!(
+                "#include cycle: {} -> {name}",
+                processing.stack.join(" -> ")
+            );
+        }
+        if !processing.included.insert(name.to_string()) {
+            return Ok(());
+        }
+
+        let source = (self.load)(name).with_context(|| format!("couldn't load {name}"))?;
+        processing.stack.push(name.to_string());
+        let file = processing.shader.files.len();
+        processing.shader.files.push(name.to_string());
+
+        let mut conditions: Vec<Condition> = Vec::new();
+        for (line_number, line) in (1..).zip(source.lines()) {
+            let keep = conditions.iter().all(|c| c.keep);
+
+            // WGSL never uses #, so any line starting with one is meant for us
+            let Some(directive) = line.trim_start().strip_prefix('#') else {
+                if keep {
+                    processing.shader.push_line(
+                        substitute(line, &processing.defines),
+                        file,
+                        line_number,
+                    );
+                }
+                continue;
+            };
+
+            let at = || format!("{name}:{line_number}");
+            let (keyword, rest) = directive
+                .split_once(char::is_whitespace)
+                .unwrap_or((directive, ""));
+            let rest = rest.trim();
+            let argument = || -> Result<&str> {
+                rest.split_whitespace()
+                    .next()
+                    .with_context(|| format!("{}: #{keyword} needs a name", at()))
+            };
+
+            match keyword {
+                "ifdef" | "ifndef" => {
+                    let defined = processing.defines.contains_key(argument()?);
+                    conditions.push(Condition {
+                        keep: defined == (keyword == "ifdef"),
+                        seen_else: false,
+                        line: line_number,
+                    });
+                }
+                "else" => {
+                    let condition = conditions
+                        .last_mut()
+                        .with_context(|| format!("{}: #else without #ifdef", at()))?;
+                    if condition.seen_else {
+                        bail!(
+                            "{}: second #else for the #ifdef on line {}",
+                            at(),
+                            condition.line
+                        );
+                    }
+                    condition.seen_else = true;
+                    condition.keep = !condition.keep;
+                }
+                "endif" => {
+                    conditions
+                        .pop()
+                        .with_context(|| format!("{}: #endif without #ifdef", at()))?;
+                }
+                // Everything else is skipped along with the lines around it
+                _ if !keep => {}
+                "include" => {
+                    let included = rest
+                        .strip_prefix('"')
+                        .and_then(|rest| rest.strip_suffix('"'))
+                        .with_context(|| {
+                            format!("{}: #include needs a \"quoted\" file name", at())
+                        })?;
+                    self.process_file(included, processing)
+                        .with_context(|| format!("included from {}", at()))?;
+                }
+                "define" => {
+                    let (define, value) =
+                        rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                    if define.is_empty() {
+                        bail!("{}: #define needs a name", at());
+                    }
+                    processing
+                        .defines
+                        .insert(define.to_string(), value.trim().to_string());
+                }
+                "undef" => {
+                    processing.defines.remove(argument()?);
+                }
+                _ => bail!("{}: unknown directive #{keyword}", at()),
+            }
+        }
+
+        if let Some(condition) = conditions.last() {
+            bail!("{name}:{}: #ifdef without #endif", condition.line);
+        }
+        processing.stack.pop();
+        Ok(())
+    }
+}
+
+impl ProcessedShader {
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
+    // The file and line that line `line` (counting from 1) of the source came from
+    pub fn origin(&self, line: u32) -> Option<(&str, u32)> {
+        let (file, line) = *self.lines.get(line.checked_sub(1)? as usize)?;
+        Some((&self.files[file], line))
+    }
+
+    // Parses and validates the source with naga, so a broken shader is caught with
+    // an error pointing at the original file and line before wgpu sees it.
+    pub fn validate(&self) -> Result<()> {
+        self.reflect().map(|_| ())
+    }
+
+    // Same as ShaderReflection::new, but with errors in terms of the original files
+    pub fn reflect(&self) -> Result<ShaderReflection> {
+        ShaderReflection::parse(&self.source, |location, message| {
+            let origin = location.and_then(|location| {
+                Some((self.origin(location.line_number)?, location.line_position))
+            });
+            match origin {
+                Some(((file, line), column)) => anyhow!("{file}:{line}:{column}: {message}"),
+                None => anyhow!(
+                    "{}: {message}",
+                    self.files.first().map_or("<shader>", |file| file)
+                ),
+            }
+        })
+    }
+
+    fn push_line(&mut self, line: Cow<str>, file: usize, line_number: u32) {
+        self.source.push_str(&line);
+        self.source.push('\n');
+        self.lines.push((file, line_number));
+    }
+}
+
+// Replaces every whole word that's been #defined with a value
+fn substitute<'l>(line: &'l str, defines: &HashMap<String, String>) -> Cow<'l, str> {
+    if defines.values().all(String::is_empty) {
+        return Cow::Borrowed(line);
+    }
+
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        let word = &rest[..end];
+        match defines.get(word) {
+            Some(value) if !value.is_empty() => out.push_str(value),
+            _ => out.push_str(word),
+        }
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
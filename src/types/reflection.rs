@@ -0,0 +1,527 @@
+use std::{collections::BTreeMap, num::NonZeroU32};
+
+use anyhow::{Context, Result, anyhow, bail, ensure};
+
+// What a shader expects to be bound and fed to it, read from its WGSL by naga so
+// layouts don't have to be kept in step with the shader by hand.
+pub struct ShaderReflection {
+    module: naga::Module,
+    info: naga::valid::ModuleInfo,
+    // Binding types set by hand, by (group, binding)
+    overrides: BTreeMap<(u32, u32), wgpu::BindingType>,
+}
+
+impl ShaderReflection {
+    // Errors give the line and column in `source`
+    pub fn new(source: &str) -> Result<Self> {
+        Self::parse(source, |location, message| match location {
+            Some(location) => anyhow!(
+                "{}:{}: {message}",
+                location.line_number,
+                location.line_position
+            ),
+            None => anyhow!(message),
+        })
+    }
+
+    // `error_at` turns a message and where in `source` it's about into an error
+    pub(crate) fn parse(
+        source: &str,
+        error_at: impl Fn(Option<naga::SourceLocation>, String) -> anyhow::Error,
+    ) -> Result<Self> {
+        let module = naga::front::wgsl::parse_str(source)
+            .map_err(|e| error_at(e.location(source), e.message().to_string()))?;
+
+        let info = naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::all(),
+        )
+        .validate(&module)
+        .map_err(|e| {
+            // Validation errors nest, and the innermost is usually the most useful
+            let mut message = e.as_inner().to_string();
+            let mut inner = std::error::Error::source(e.as_inner());
+            while let Some(error) = inner {
+                message += &format!(": {error}");
+                inner = error.source();
+            }
+            error_at(e.location(source), message)
+        })?;
+
+        Ok(Self {
+            module,
+            info,
+            overrides: BTreeMap::new(),
+        })
+    }
+
+    // The layout entries for every binding the shader declares in `group`, in binding
+    // order. Each is visible to the stages whose entry points use it, or to all of
+    // them if none do. WGSL doesn't say whether a float texture will be filtered, so
+    // they're all taken to be filterable, and plain samplers to be Filtering, unless
+    // set_binding_type says otherwise.
+    pub fn bind_group_layout_entries(&self, group: u32) -> Result<Vec<wgpu::BindGroupLayoutEntry>> {
+        let mut entries = Vec::new();
+        for (handle, global) in self.module.global_variables.iter() {
+            let Some(binding) = global
+                .binding
+                .as_ref()
+                .filter(|binding| binding.group == group)
+            else {
+                continue;
+            };
+            let name = global.name.as_deref().unwrap_or("<unnamed>");
+            let (ty, count) = self
+                .binding_type(global)
+                .with_context(|| format!("@group({group}) @binding({}) {name}", binding.binding))?;
+            let ty = self
+                .overrides
+                .get(&(group, binding.binding))
+                .copied()
+                .unwrap_or(ty);
+
+            let mut visibility = wgpu::ShaderStages::NONE;
+            let mut all_stages = wgpu::ShaderStages::NONE;
+            for (index, entry_point) in self.module.entry_points.iter().enumerate() {
+                let stage = shader_stage(entry_point.stage);
+                all_stages |= stage;
+                if !self.info.get_entry_point(index)[handle].is_empty() {
+                    visibility |= stage;
+                }
+            }
+
+            entries.push(wgpu::BindGroupLayoutEntry {
+                binding: binding.binding,
+                visibility: if visibility.is_empty() {
+                    all_stages
+                } else {
+                    visibility
+                },
+                ty,
+                count,
+            });
+        }
+        entries.sort_by_key(|entry| entry.binding);
+        Ok(entries)
+    }
+
+    // Uses `ty` for a binding instead of what would be worked out from the shader,
+    // for what WGSL can't say: unfilterable float textures (like Rgba32Float) and
+    // the NonFiltering samplers they need, or buffers with dynamic offsets. It has
+    // to be the same kind of binding the shader declares.
+    pub fn set_binding_type(
+        &mut self,
+        group: u32,
+        binding: u32,
+        ty: wgpu::BindingType,
+    ) -> Result<()> {
+        use wgpu::{BindingType as B, SamplerBindingType::Comparison, TextureSampleType::Float};
+
+        let global = self
+            .module
+            .global_variables
+            .iter()
+            .map(|(_, global)| global)
+            .find(|global| {
+                global
+                    .binding
+                    .as_ref()
+                    .is_some_and(|b| b.group == group && b.binding == binding)
+            })
+            .with_context(|| format!("there's nothing at @group({group}) @binding({binding})"))?;
+        let (declared, _) = self.binding_type(global)?;
+
+        let compatible = match (declared, ty) {
+            (B::Buffer { ty: a, .. }, B::Buffer { ty: b, .. }) => a == b,
+            (B::Sampler(a), B::Sampler(b)) => (a == Comparison) == (b == Comparison),
+            (
+                B::Texture {
+                    sample_type: a,
+                    view_dimension: a_dimension,
+                    multisampled: a_multi,
+                },
+                B::Texture {
+                    sample_type: b,
+                    view_dimension: b_dimension,
+                    multisampled: b_multi,
+                },
+            ) => {
+                (a == b || matches!((a, b), (Float { .. }, Float { .. })))
+                    && a_dimension == b_dimension
+                    && a_multi == b_multi
+            }
+            (a, b) => a == b,
+        };
+        ensure!(
+            compatible,
+            "@group({group}) @binding({binding}) is declared as {declared:?} in the shader, which can't be bound as {ty:?}"
+        );
+
+        self.overrides.insert((group, binding), ty);
+        Ok(())
+    }
+
+    // Checks that every @location the vertex entry point reads is one of the
+    // attributes in `buffers`, with the same kind of scalar (float, sint or uint).
+    // The number of components can differ, WebGPU fills in or drops the extras.
+    pub fn check_vertex_inputs(
+        &self,
+        entry_point: &str,
+        buffers: &[wgpu::VertexBufferLayout],
+    ) -> Result<()> {
+        let function = &self
+            .module
+            .entry_points
+            .iter()
+            .find(|ep| ep.name == entry_point && ep.stage == naga::ShaderStage::Vertex)
+            .with_context(|| format!("there's no vertex entry point called {entry_point}"))?
+            .function;
+
+        let attributes: BTreeMap<u32, wgpu::VertexFormat> = buffers
+            .iter()
+            .flat_map(|buffer| buffer.attributes)
+            .map(|attribute| (attribute.shader_location, attribute.format))
+            .collect();
+
+        // Inputs are either arguments of their own, or members of a struct argument
+        let mut inputs = Vec::new();
+        for argument in &function.arguments {
+            match &self.module.types[argument.ty].inner {
+                naga::TypeInner::Struct { members, .. } if argument.binding.is_none() => {
+                    inputs.extend(members.iter().map(|member| {
+                        (member.name.as_deref(), member.ty, member.binding.as_ref())
+                    }));
+                }
+                _ => inputs.push((
+                    argument.name.as_deref(),
+                    argument.ty,
+                    argument.binding.as_ref(),
+                )),
+            }
+        }
+
+        for (name, ty, binding) in inputs {
+            let Some(&naga::Binding::Location { location, .. }) = binding else {
+                continue;
+            };
+            let name = name.unwrap_or("<unnamed>");
+            let type_name = self.type_name(ty);
+
+            let Some(&format) = attributes.get(&location) else {
+                bail!(
+                    "{entry_point} reads @location({location}) {name}: {type_name}, but none of the vertex buffers have an attribute at that location"
+                );
+            };
+            let kind = match self.module.types[ty].inner {
+                naga::TypeInner::Scalar(scalar) | naga::TypeInner::Vector { scalar, .. } => {
+                    scalar.kind
+                }
+                _ => bail!(
+                    "{entry_point} reads @location({location}) {name} as a {type_name}, which can't be a vertex input"
+                ),
+            };
+            if kind != format_kind(format) {
+                bail!(
+                    "{entry_point} reads @location({location}) {name} as a {type_name}, but the vertex attribute there is {format:?}"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    // Checks that the fragment entry point writes every color target (unless its
+    // write mask is empty) with the kind of scalar the target's format holds, and
+    // at least as many components. Outputs that have no target are just dropped.
+    pub fn check_fragment_outputs(
+        &self,
+        entry_point: &str,
+        targets: &[Option<wgpu::ColorTargetState>],
+    ) -> Result<()> {
+        let function = &self
+            .module
+            .entry_points
+            .iter()
+            .find(|ep| ep.name == entry_point && ep.stage == naga::ShaderStage::Fragment)
+            .with_context(|| format!("there's no fragment entry point called {entry_point}"))?
+            .function;
+
+        // The result is either a single output, or a struct of them
+        let mut outputs = BTreeMap::new();
+        if let Some(result) = &function.result {
+            match &self.module.types[result.ty].inner {
+                naga::TypeInner::Struct { members, .. } if result.binding.is_none() => {
+                    for member in members {
+                        if let Some(&naga::Binding::Location { location, .. }) =
+                            member.binding.as_ref()
+                        {
+                            outputs.insert(
+                                location,
+                                (member.name.as_deref().unwrap_or("<unnamed>"), member.ty),
+                            );
+                        }
+                    }
+                }
+                _ => {
+                    if let Some(&naga::Binding::Location { location, .. }) = result.binding.as_ref()
+                    {
+                        outputs.insert(location, ("its result", result.ty));
+                    }
+                }
+            }
+        }
+
+        for (location, target) in targets.iter().enumerate() {
+            let Some(target) = target else { continue };
+            let format = target.format;
+            let Some((name, ty)) = outputs.get(&(location as u32)).copied() else {
+                ensure!(
+                    target.write_mask.is_empty(),
+                    "{entry_point} doesn't write @location({location}), but there's a {format:?} color target there"
+                );
+                continue;
+            };
+
+            let type_name = self.type_name(ty);
+            let (kind, components) = match self.module.types[ty].inner {
+                naga::TypeInner::Scalar(scalar) => (scalar.kind, 1),
+                naga::TypeInner::Vector { size, scalar } => (scalar.kind, size as u8),
+                _ => bail!(
+                    "{entry_point} writes @location({location}) {name} as a {type_name}, which can't be a color output"
+                ),
+            };
+            let expected = match format.sample_type(None, None) {
+                Some(wgpu::TextureSampleType::Float { .. }) if format.has_color_aspect() => {
+                    naga::ScalarKind::Float
+                }
+                Some(wgpu::TextureSampleType::Sint) => naga::ScalarKind::Sint,
+                Some(wgpu::TextureSampleType::Uint) => naga::ScalarKind::Uint,
+                _ => bail!("{format:?} isn't a color format, so it can't be a color target"),
+            };
+            ensure!(
+                kind == expected && components >= format.components(),
+                "{entry_point} writes @location({location}) {name} as a {type_name}, which doesn't fit the {format:?} color target there"
+            );
+        }
+        Ok(())
+    }
+
+    pub fn has_entry_point(&self, name: &str, stage: wgpu::ShaderStages) -> bool {
+        self.module
+            .entry_points
+            .iter()
+            .any(|ep| ep.name == name && shader_stage(ep.stage) == stage)
+    }
+
+    // Whether there's an `override` that a pipeline constant called `key` would
+    // set, going by either its name or its @id
+    pub fn has_override(&self, key: &str) -> bool {
+        self.module.overrides.iter().any(|(_, o)| {
+            o.name.as_deref() == Some(key) || o.id.is_some_and(|id| id.to_string() == key)
+        })
+    }
+
+    fn binding_type(
+        &self,
+        global: &naga::GlobalVariable,
+    ) -> Result<(wgpu::BindingType, Option<NonZeroU32>)> {
+        // Arrays of textures or samplers are one binding, `count` long
+        let (ty, count) = match self.module.types[global.ty].inner {
+            naga::TypeInner::BindingArray { base, size } => match size {
+                naga::ArraySize::Constant(size) => (base, Some(size)),
+                _ => bail!("binding arrays need a constant size"),
+            },
+            _ => (global.ty, None),
+        };
+
+        let binding_type = match global.space {
+            naga::AddressSpace::Uniform => buffer_binding(wgpu::BufferBindingType::Uniform),
+            naga::AddressSpace::Storage { access } => {
+                buffer_binding(wgpu::BufferBindingType::Storage {
+                    read_only: !access.contains(naga::StorageAccess::STORE),
+                })
+            }
+            naga::AddressSpace::Handle => match self.module.types[ty].inner {
+                naga::TypeInner::Sampler { comparison: true } => {
+                    wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison)
+                }
+                naga::TypeInner::Sampler { comparison: false } => {
+                    wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering)
+                }
+                naga::TypeInner::Image {
+                    dim,
+                    arrayed,
+                    class,
+                } => {
+                    let view_dimension = view_dimension(dim, arrayed)?;
+                    match class {
+                        naga::ImageClass::Sampled { kind, multi } => wgpu::BindingType::Texture {
+                            sample_type: match kind {
+                                naga::ScalarKind::Sint => wgpu::TextureSampleType::Sint,
+                                naga::ScalarKind::Uint => wgpu::TextureSampleType::Uint,
+                                // Multisampled textures can't be filtered
+                                _ => wgpu::TextureSampleType::Float { filterable: !multi },
+                            },
+                            view_dimension,
+                            multisampled: multi,
+                        },
+                        naga::ImageClass::Depth { multi } => wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Depth,
+                            view_dimension,
+                            multisampled: multi,
+                        },
+                        naga::ImageClass::Storage { format, access } => {
+                            wgpu::BindingType::StorageTexture {
+                                access: match (
+                                    access.contains(naga::StorageAccess::LOAD),
+                                    access.contains(naga::StorageAccess::STORE),
+                                ) {
+                                    (true, true) => wgpu::StorageTextureAccess::ReadWrite,
+                                    (true, false) => wgpu::StorageTextureAccess::ReadOnly,
+                                    _ => wgpu::StorageTextureAccess::WriteOnly,
+                                },
+                                format: storage_format(format)?,
+                                view_dimension,
+                            }
+                        }
+                    }
+                }
+                ref other => bail!("{other:?} can't be bound"),
+            },
+            space => bail!("variables in {space:?} space can't be bound"),
+        };
+        Ok((binding_type, count))
+    }
+
+    // How the type is written in WGSL, for scalars and vectors at least
+    fn type_name(&self, ty: naga::Handle<naga::Type>) -> String {
+        match self.module.types[ty].inner {
+            naga::TypeInner::Scalar(scalar) => scalar_name(scalar),
+            naga::TypeInner::Vector { size, scalar } => {
+                format!("vec{}<{}>", size as u8, scalar_name(scalar))
+            }
+            ref other => self.module.types[ty]
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("{other:?}")),
+        }
+    }
+}
+
+// Combines the entries several shaders need from the same bind group into one
+// layout, visible to every stage that uses each binding. The shaders have to agree
+// on what's at each binding.
+pub fn merge_entries(
+    shaders: impl IntoIterator<Item = Vec<wgpu::BindGroupLayoutEntry>>,
+) -> Result<Vec<wgpu::BindGroupLayoutEntry>> {
+    let mut merged: BTreeMap<u32, wgpu::BindGroupLayoutEntry> = BTreeMap::new();
+    for entry in shaders.into_iter().flatten() {
+        match merged.get_mut(&entry.binding) {
+            Some(existing) if existing.ty != entry.ty || existing.count != entry.count => {
+                bail!(
+                    "@binding({}) is {:?} in one shader and {:?} in another",
+                    entry.binding,
+                    existing.ty,
+                    entry.ty
+                );
+            }
+            Some(existing) => existing.visibility |= entry.visibility,
+            None => {
+                merged.insert(entry.binding, entry);
+            }
+        }
+    }
+    Ok(merged.into_values().collect())
+}
+
+fn buffer_binding(ty: wgpu::BufferBindingType) -> wgpu::BindingType {
+    wgpu::BindingType::Buffer {
+        ty,
+        has_dynamic_offset: false,
+        min_binding_size: None,
+    }
+}
+
+fn shader_stage(stage: naga::ShaderStage) -> wgpu::ShaderStages {
+    match stage {
+        naga::ShaderStage::Vertex => wgpu::ShaderStages::VERTEX,
+        naga::ShaderStage::Fragment => wgpu::ShaderStages::FRAGMENT,
+        naga::ShaderStage::Compute => wgpu::ShaderStages::COMPUTE,
+    }
+}
+
+fn view_dimension(dim: naga::ImageDimension, arrayed: bool) -> Result<wgpu::TextureViewDimension> {
+    Ok(match (dim, arrayed) {
+        (naga::ImageDimension::D1, false) => wgpu::TextureViewDimension::D1,
+        (naga::ImageDimension::D2, false) => wgpu::TextureViewDimension::D2,
+        (naga::ImageDimension::D2, true) => wgpu::TextureViewDimension::D2Array,
+        (naga::ImageDimension::D3, false) => wgpu::TextureViewDimension::D3,
+        (naga::ImageDimension::Cube, false) => wgpu::TextureViewDimension::Cube,
+        (naga::ImageDimension::Cube, true) => wgpu::TextureViewDimension::CubeArray,
+        (dim, true) => bail!("{dim:?} textures can't be arrayed"),
+    })
+}
+
+// The storage texture formats wgpu supports without extra features
+fn storage_format(format: naga::StorageFormat) -> Result<wgpu::TextureFormat> {
+    use naga::StorageFormat as S;
+    use wgpu::TextureFormat as T;
+    Ok(match format {
+        S::Rgba8Unorm => T::Rgba8Unorm,
+        S::Rgba8Snorm => T::Rgba8Snorm,
+        S::Rgba8Uint => T::Rgba8Uint,
+        S::Rgba8Sint => T::Rgba8Sint,
+        S::Bgra8Unorm => T::Bgra8Unorm,
+        S::Rgba16Uint => T::Rgba16Uint,
+        S::Rgba16Sint => T::Rgba16Sint,
+        S::Rgba16Float => T::Rgba16Float,
+        S::R32Uint => T::R32Uint,
+        S::R32Sint => T::R32Sint,
+        S::R32Float => T::R32Float,
+        S::Rg32Uint => T::Rg32Uint,
+        S::Rg32Sint => T::Rg32Sint,
+        S::Rg32Float => T::Rg32Float,
+        S::Rgba32Uint => T::Rgba32Uint,
+        S::Rgba32Sint => T::Rgba32Sint,
+        S::Rgba32Float => T::Rgba32Float,
+        other => bail!("{other:?} storage textures aren't supported"),
+    })
+}
+
+fn format_kind(format: wgpu::VertexFormat) -> naga::ScalarKind {
+    use wgpu::VertexFormat as F;
+    match format {
+        F::Uint8
+        | F::Uint8x2
+        | F::Uint8x4
+        | F::Uint16
+        | F::Uint16x2
+        | F::Uint16x4
+        | F::Uint32
+        | F::Uint32x2
+        | F::Uint32x3
+        | F::Uint32x4 => naga::ScalarKind::Uint,
+        F::Sint8
+        | F::Sint8x2
+        | F::Sint8x4
+        | F::Sint16
+        | F::Sint16x2
+        | F::Sint16x4
+        | F::Sint32
+        | F::Sint32x2
+        | F::Sint32x3
+        | F::Sint32x4 => naga::ScalarKind::Sint,
+        // Normalized formats are read as floats
+        _ => naga::ScalarKind::Float,
+    }
+}
+
+fn scalar_name(scalar: naga::Scalar) -> String {
+    let prefix = match scalar.kind {
+        naga::ScalarKind::Sint => "i",
+        naga::ScalarKind::Uint => "u",
+        naga::ScalarKind::Float => "f",
+        naga::ScalarKind::Bool => return "bool".to_string(),
+        _ => return format!("{scalar:?}"),
+    };
+    format!("{prefix}{}", scalar.width * 8)
+}
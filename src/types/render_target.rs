@@ -0,0 +1,83 @@
+use winit::window::Window;
+
+// Where State draws its frames: either the window's swapchain, or a plain
+// texture that lives on the GPU with no window attached (headless).
+pub enum RenderTarget<'a> {
+    Surface {
+        surface: wgpu::Surface<'a>,
+        window: &'a Window,
+    },
+    Offscreen(OffscreenTarget),
+}
+
+pub struct OffscreenTarget {
+    pub texture: wgpu::Texture,
+}
+
+impl OffscreenTarget {
+    pub fn new(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen Target"),
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: config.usage,
+            view_formats: &[],
+        });
+
+        Self { texture }
+    }
+}
+
+// A frame that has been acquired from a RenderTarget and is ready to be drawn into.
+pub enum Frame<'t> {
+    Surface(wgpu::SurfaceTexture),
+    Offscreen(&'t wgpu::Texture),
+}
+
+impl Frame<'_> {
+    pub fn texture(&self) -> &wgpu::Texture {
+        match self {
+            Frame::Surface(output) => &output.texture,
+            Frame::Offscreen(texture) => texture,
+        }
+    }
+
+    pub fn present(self) {
+        // Offscreen textures have nothing to present to; the pixels just stay in the texture.
+        if let Frame::Surface(output) = self {
+            output.present();
+        }
+    }
+}
+
+impl<'a> RenderTarget<'a> {
+    pub fn window(&self) -> Option<&'a Window> {
+        match self {
+            RenderTarget::Surface { window, .. } => Some(window),
+            RenderTarget::Offscreen(_) => None,
+        }
+    }
+
+    pub fn acquire(&self) -> Result<Frame<'_>, wgpu::SurfaceError> {
+        match self {
+            RenderTarget::Surface { surface, .. } => {
+                Ok(Frame::Surface(surface.get_current_texture()?))
+            }
+            RenderTarget::Offscreen(target) => Ok(Frame::Offscreen(&target.texture)),
+        }
+    }
+
+    pub fn configure(&mut self, device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) {
+        match self {
+            RenderTarget::Surface { surface, .. } => surface.configure(device, config),
+            RenderTarget::Offscreen(target) => *target = OffscreenTarget::new(device, config),
+        }
+    }
+}
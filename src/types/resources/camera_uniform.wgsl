@@ -0,0 +1,14 @@
+// The camera's matrices and position, as written by CameraUniform. It's bound
+// at group 1 unless CAMERA_GROUP is defined before this is included.
+
+#ifndef CAMERA_GROUP
+#define CAMERA_GROUP 1
+#endif
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+    view_position: vec4<f32>,
+    inv_view_proj: mat4x4<f32>,
+};
+@group(CAMERA_GROUP) @binding(0)
+var<uniform> camera: CameraUniform;
@@ -0,0 +1,74 @@
+// Renders one face of a cubemap by looking up the direction of each of its
+// texels in an equirectangular (latitude/longitude) panorama.
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// A single triangle big enough to cover the whole face, no vertex buffer needed
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+struct Face {
+    // 0 - 5 for +X, -X, +Y, -Y, +Z, -Z
+    index: u32,
+    _padding0: u32,
+    _padding1: u32,
+    _padding2: u32,
+};
+
+@group(0) @binding(0)
+var t_equirect: texture_2d<f32>;
+@group(0) @binding(1)
+var<uniform> face: Face;
+
+const PI: f32 = 3.14159265358979;
+
+// The direction through a point on a face, using the same layout the GPU uses
+// when it samples a cubemap. v runs down each face.
+fn face_direction(index: u32, uv: vec2<f32>) -> vec3<f32> {
+    let st = uv * 2.0 - 1.0;
+    switch index {
+        case 0u: { return vec3<f32>(1.0, -st.y, -st.x); }
+        case 1u: { return vec3<f32>(-1.0, -st.y, st.x); }
+        case 2u: { return vec3<f32>(st.x, 1.0, st.y); }
+        case 3u: { return vec3<f32>(st.x, -1.0, -st.y); }
+        case 4u: { return vec3<f32>(st.x, -st.y, 1.0); }
+        default: { return vec3<f32>(-st.x, -st.y, -1.0); }
+    }
+}
+
+// Bilinear filtering done by hand, since 32 bit float textures usually can't be
+// filtered by the sampler
+fn sample_equirect(uv: vec2<f32>) -> vec4<f32> {
+    let size = vec2<i32>(textureDimensions(t_equirect));
+    let texel = uv * vec2<f32>(size) - 0.5;
+    let base = vec2<i32>(floor(texel));
+    let f = texel - floor(texel);
+
+    // Left and right wrap around to meet each other, the top and bottom rows are the poles
+    let x0 = ((base.x % size.x) + size.x) % size.x;
+    let x1 = (x0 + 1) % size.x;
+    let y0 = clamp(base.y, 0, size.y - 1);
+    let y1 = clamp(base.y + 1, 0, size.y - 1);
+
+    let top = mix(textureLoad(t_equirect, vec2<i32>(x0, y0), 0), textureLoad(t_equirect, vec2<i32>(x1, y0), 0), f.x);
+    let bottom = mix(textureLoad(t_equirect, vec2<i32>(x0, y1), 0), textureLoad(t_equirect, vec2<i32>(x1, y1), 0), f.x);
+    return mix(top, bottom, f.y);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let direction = normalize(face_direction(face.index, in.uv));
+    // The middle of the panorama faces +X, and its top row is straight up
+    let uv = vec2<f32>(atan2(direction.z, direction.x) / (2.0 * PI) + 0.5, acos(direction.y) / PI);
+    return vec4<f32>(sample_equirect(uv).rgb, 1.0);
+}
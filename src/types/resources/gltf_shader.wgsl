@@ -0,0 +1,88 @@
+// Vertex shader
+
+#include "camera_uniform.wgsl"
+
+#define LIGHTS_GROUP 2
+#include "lights.wgsl"
+
+// A PbrVertex. The tangent is there for normal maps, which aren't drawn yet.
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) normal: vec3<f32>,
+    @location(3) tangent: vec4<f32>,
+}
+
+struct InstanceInput {
+    @location(5) model_matrix_0: vec4<f32>,
+    @location(6) model_matrix_1: vec4<f32>,
+    @location(7) model_matrix_2: vec4<f32>,
+    @location(8) model_matrix_3: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) world_normal: vec3<f32>,
+    @location(2) world_position: vec3<f32>,
+}
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+    instance: InstanceInput,
+) -> VertexOutput {
+    let model_matrix = mat4x4<f32>(
+        instance.model_matrix_0,
+        instance.model_matrix_1,
+        instance.model_matrix_2,
+        instance.model_matrix_3,
+    );
+    // Only correct for uniform scaling, otherwise this needs the inverse transpose
+    let normal_matrix = mat3x3<f32>(
+        instance.model_matrix_0.xyz,
+        instance.model_matrix_1.xyz,
+        instance.model_matrix_2.xyz,
+    );
+
+    let world_position = model_matrix * vec4<f32>(model.position, 1.0);
+
+    var out: VertexOutput;
+    out.tex_coords = model.tex_coords;
+    out.world_normal = normalize(normal_matrix * model.normal);
+    out.world_position = world_position.xyz;
+    out.clip_position = camera.view_proj * world_position;
+    return out;
+}
+
+// Fragment shader
+
+// The factors of a PbrMaterial, as written by PbrMaterialUniform
+struct Material {
+    base_color_factor: vec4<f32>,
+    emissive_factor: vec3<f32>,
+}
+
+@group(0) @binding(0)
+var t_base_color: texture_2d<f32>;
+@group(0) @binding(1)
+var s_base_color: sampler;
+@group(0) @binding(2)
+var<uniform> material: Material;
+// White when the material has no emissive texture, so the factor is used as it is
+@group(0) @binding(3)
+var t_emissive: texture_2d<f32>;
+@group(0) @binding(4)
+var s_emissive: sampler;
+
+#include "lighting.wgsl"
+
+// Only the base color and emission are used so far, metalness and roughness
+// are left to the Blinn-Phong lighting the rest of the scene has
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let base_color = textureSample(t_base_color, s_base_color, in.tex_coords) * material.base_color_factor;
+    let emissive = textureSample(t_emissive, s_emissive, in.tex_coords).xyz * material.emissive_factor;
+    let color = lighting(normalize(in.world_normal), in.world_position);
+    return vec4<f32>(color * base_color.xyz + emissive, base_color.a);
+}
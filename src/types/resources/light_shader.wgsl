@@ -0,0 +1,46 @@
+// Draws a small unlit cube where each light is, so they can be seen in the scene.
+// One instance is drawn per light.
+
+// Vertex shader
+
+#define CAMERA_GROUP 0
+#include "camera_uniform.wgsl"
+
+#define LIGHTS_GROUP 1
+#include "lights.wgsl"
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec3<f32>,
+};
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+    @builtin(instance_index) light_index: u32,
+) -> VertexOutput {
+    let light = lights[light_index];
+
+    var out: VertexOutput;
+    out.color = light.color;
+    if light.kind == LIGHT_DIRECTIONAL {
+        // Directional lights have no position to draw at, so they're moved
+        // outside of clip space and get clipped away
+        out.clip_position = vec4<f32>(2.0, 2.0, 2.0, 1.0);
+    } else {
+        let scale = 0.25;
+        out.clip_position = camera.view_proj * vec4<f32>(model.position * scale + light.position, 1.0);
+    }
+    return out;
+}
+
+// Fragment shader
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(in.color, 1.0);
+}
@@ -0,0 +1,92 @@
+// Blinn-Phong lighting from every light in the scene, with the shadow map
+// applied to the light that casts shadows when SHADOWS is defined. Needs the
+// camera and the lights included first; the shadow map is bound at group 3.
+
+#ifdef SHADOWS
+struct ShadowUniform {
+    light_view_proj: mat4x4<f32>,
+    // Which of the lights casts the shadow, -1 if none of them do
+    light_index: i32,
+    texel_size: f32,
+}
+@group(3) @binding(0)
+var t_shadow: texture_depth_2d;
+@group(3) @binding(1)
+var s_shadow: sampler_comparison;
+@group(3) @binding(2)
+var<uniform> shadow: ShadowUniform;
+
+// 1.0 when fully lit, 0.0 when fully in shadow
+fn shadow_factor(world_position: vec3<f32>) -> f32 {
+    let light_position = shadow.light_view_proj * vec4<f32>(world_position, 1.0);
+    let ndc = light_position.xyz / light_position.w;
+    // Clip space has y pointing up, textures have it pointing down
+    let uv = ndc.xy * vec2<f32>(0.5, -0.5) + 0.5;
+
+    // Anything the shadow map doesn't cover is lit
+    if any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0)) || ndc.z > 1.0 {
+        return 1.0;
+    }
+
+    // Percentage closer filtering: average the comparison over a 3x3 block of
+    // texels so shadow edges come out soft instead of jagged
+    var lit = 0.0;
+    for (var y = -1; y <= 1; y += 1) {
+        for (var x = -1; x <= 1; x += 1) {
+            let offset = vec2<f32>(f32(x), f32(y)) * shadow.texel_size;
+            lit += textureSampleCompareLevel(t_shadow, s_shadow, uv + offset, ndc.z);
+        }
+    }
+    return lit / 9.0;
+}
+#endif
+
+// Diffuse and specular light reaching a point from a single light
+fn light_contribution(light: Light, normal: vec3<f32>, world_position: vec3<f32>, view_dir: vec3<f32>) -> vec3<f32> {
+    var light_dir: vec3<f32>;
+    var attenuation = 1.0;
+
+    if light.kind == LIGHT_DIRECTIONAL {
+        light_dir = normalize(-light.direction);
+    } else {
+        let to_light = light.position - world_position;
+        let distance = length(to_light);
+        light_dir = to_light / distance;
+
+        // Inverse square falloff, smoothly brought down to zero at the light's range
+        let window = clamp(1.0 - pow(distance / light.range, 4.0), 0.0, 1.0);
+        attenuation = window * window / (distance * distance + 1.0);
+
+        if light.kind == LIGHT_SPOT {
+            let cos_angle = dot(-light_dir, normalize(light.direction));
+            attenuation *= smoothstep(light.outer_cone_cos, light.inner_cone_cos, cos_angle);
+        }
+    }
+
+    // Blinn-Phong uses the half vector instead of reflecting the light direction
+    let half_dir = normalize(view_dir + light_dir);
+
+    let diffuse_strength = max(dot(normal, light_dir), 0.0);
+    let specular_strength = pow(max(dot(normal, half_dir), 0.0), 32.0);
+
+    return light.color * (diffuse_strength + specular_strength) * light.intensity * attenuation;
+}
+
+// Everything reaching a point from all of the lights, to be multiplied by the
+// surface's color
+fn lighting(normal: vec3<f32>, world_position: vec3<f32>) -> vec3<f32> {
+    let view_dir = normalize(camera.view_position.xyz - world_position);
+
+    // A little light reaches everything, even the sides facing away
+    var color = vec3<f32>(0.1);
+    for (var i = 0u; i < light_count.count; i += 1u) {
+        var contribution = light_contribution(lights[i], normal, world_position, view_dir);
+#ifdef SHADOWS
+        if i32(i) == shadow.light_index {
+            contribution *= shadow_factor(world_position);
+        }
+#endif
+        color += contribution;
+    }
+    return color;
+}
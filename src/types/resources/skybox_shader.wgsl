@@ -0,0 +1,37 @@
+// Draws a cubemap around the whole scene. A single triangle covers the screen and
+// each pixel looks up the direction the camera sees through it.
+
+#include "camera_uniform.wgsl"
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) ndc: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+
+    var out: VertexOutput;
+    out.ndc = uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0);
+    // The depth doesn't matter, the pipeline neither tests nor writes it
+    out.clip_position = vec4<f32>(out.ndc, 0.5, 1.0);
+    return out;
+}
+
+// Fragment shader
+
+@group(0) @binding(0)
+var t_sky: texture_cube<f32>;
+@group(0) @binding(1)
+var s_sky: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Unprojecting a point halfway into the depth range gives somewhere in front
+    // of the camera whatever the projection, even with reverse-Z or an infinite
+    // far plane, where the far plane itself can't be unprojected.
+    let point = camera.inv_view_proj * vec4<f32>(in.ndc, 0.5, 1.0);
+    let direction = point.xyz / point.w - camera.view_position.xyz;
+    return textureSample(t_sky, s_sky, direction);
+}
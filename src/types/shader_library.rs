@@ -0,0 +1,153 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Context;
+use web_time::Instant;
+
+use super::preprocessor::Preprocessor;
+
+// The shaders State builds its pipelines from, by file name in src/types/resources
+pub const INSTANCED_SHADER: &str = "instanced_shader.wgsl";
+pub const LIT_SHADER: &str = "lit_shader.wgsl";
+pub const LIGHT_SHADER: &str = "light_shader.wgsl";
+pub const SHADOW_SHADER: &str = "shadow_shader.wgsl";
+pub const SKYBOX_SHADER: &str = "skybox_shader.wgsl";
+pub const GLTF_SHADER: &str = "gltf_shader.wgsl";
+
+const EMBEDDED: [(&str, &str); 9] = [
+    (
+        INSTANCED_SHADER,
+        include_str!("resources/instanced_shader.wgsl"),
+    ),
+    (LIT_SHADER, include_str!("resources/lit_shader.wgsl")),
+    (LIGHT_SHADER, include_str!("resources/light_shader.wgsl")),
+    (SHADOW_SHADER, include_str!("resources/shadow_shader.wgsl")),
+    (SKYBOX_SHADER, include_str!("resources/skybox_shader.wgsl")),
+    (GLTF_SHADER, include_str!("resources/gltf_shader.wgsl")),
+    // Only ever #included
+    (
+        "camera_uniform.wgsl",
+        include_str!("resources/camera_uniform.wgsl"),
+    ),
+    (
+        "textured_vertex.wgsl",
+        include_str!("resources/textured_vertex.wgsl"),
+    ),
+    ("lighting.wgsl", include_str!("resources/lighting.wgsl")),
+];
+
+// Where shader source comes from. Normally that's the copies built into the
+// binary, but on native builds they can be read from a directory instead, which
+// is checked for edits so the shaders can be changed without a rebuild.
+pub struct ShaderLibrary {
+    dir: Option<PathBuf>,
+    // When each file was last modified, as of the last poll
+    modified: HashMap<&'static str, SystemTime>,
+    last_poll: Option<Instant>,
+    // How often poll_changes actually looks at the files
+    pub poll_interval: Duration,
+}
+
+impl Default for ShaderLibrary {
+    fn default() -> Self {
+        Self::embedded()
+    }
+}
+
+impl ShaderLibrary {
+    pub fn embedded() -> Self {
+        Self {
+            dir: None,
+            modified: HashMap::new(),
+            last_poll: None,
+            poll_interval: Duration::from_millis(250),
+        }
+    }
+
+    // Reads the shaders from `dir` (usually src/types/resources), which has to
+    // hold a file for every one of them.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_dir(dir: impl Into<PathBuf>) -> Self {
+        let mut library = Self {
+            dir: Some(dir.into()),
+            ..Self::embedded()
+        };
+        library.modified = library.modified_times();
+        library
+    }
+
+    pub fn is_watching(&self) -> bool {
+        self.dir.is_some()
+    }
+
+    pub fn embedded_source(name: &str) -> &'static str {
+        EMBEDDED
+            .iter()
+            .find(|(file, _)| *file == name)
+            .map(|(_, source)| *source)
+            .unwrap_or_else(|| panic!("no shader called {name}"))
+    }
+
+    pub fn source(&self, name: &str) -> anyhow::Result<Cow<'static, str>> {
+        match &self.dir {
+            Some(dir) => {
+                let path = dir.join(name);
+                let source = std::fs::read_to_string(&path)
+                    .with_context(|| format!("couldn't read {}", path.display()))?;
+                Ok(Cow::Owned(source))
+            }
+            None => Ok(Cow::Borrowed(Self::embedded_source(name))),
+        }
+    }
+
+    // Processes shaders whose #includes are looked up in this library
+    pub fn preprocessor(&self) -> Preprocessor<'_> {
+        Preprocessor::new(|name| self.source(name))
+    }
+
+    // The shaders whose files have changed since the last call. Cheap to call every
+    // frame, since it only checks the files once every poll_interval.
+    pub fn poll_changes(&mut self) -> Vec<&'static str> {
+        if self.dir.is_none() {
+            return Vec::new();
+        }
+        let now = Instant::now();
+        if self
+            .last_poll
+            .is_some_and(|last| now.duration_since(last) < self.poll_interval)
+        {
+            return Vec::new();
+        }
+        self.last_poll = Some(now);
+
+        let modified = self.modified_times();
+        let changed = modified
+            .iter()
+            .filter(|(name, time)| self.modified.get(*name) != Some(*time))
+            .map(|(name, _)| *name)
+            .collect();
+        self.modified = modified;
+        changed
+    }
+
+    // Files that can't be read right now (an editor might be halfway through
+    // saving one) are left out, so they count as changed once they're back.
+    fn modified_times(&self) -> HashMap<&'static str, SystemTime> {
+        let Some(dir) = &self.dir else {
+            return HashMap::new();
+        };
+        EMBEDDED
+            .iter()
+            .filter_map(|(name, _)| {
+                let modified = std::fs::metadata(dir.join(name))
+                    .and_then(|m| m.modified())
+                    .ok()?;
+                Some((*name, modified))
+            })
+            .collect()
+    }
+}
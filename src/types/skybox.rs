@@ -0,0 +1,155 @@
+use super::{
+    pipeline_builder::PipelineBuilder,
+    shader_library::{SKYBOX_SHADER, ShaderLibrary},
+    texture::CubeTexture,
+};
+
+// A cubemap drawn around the scene, as if it were infinitely far away
+pub struct Skybox {
+    cube: CubeTexture,
+    bind_group: wgpu::BindGroup,
+    // Kept so the pipeline can be rebuilt when its shader changes
+    layout: wgpu::PipelineLayout,
+    color_format: wgpu::TextureFormat,
+    depth_format: wgpu::TextureFormat,
+    sample_count: u32,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl Skybox {
+    // `camera_layout` is the layout of the camera bind group that'll be passed
+    // to draw. The pipeline renders into `color_format` inside a pass with a
+    // `depth_format` depth attachment, both with `sample_count` samples.
+    pub fn new(
+        device: &wgpu::Device,
+        cube: CubeTexture,
+        camera_layout: &wgpu::BindGroupLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Self {
+        let shader = ShaderLibrary::embedded()
+            .preprocessor()
+            .process(SKYBOX_SHADER)
+            .expect("the built in skybox shader is broken");
+        // The cube texture and its sampler, as the shader declares them
+        let entries = shader
+            .reflect()
+            .unwrap()
+            .bind_group_layout_entries(0)
+            .unwrap();
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &entries,
+            label: Some("skybox_bind_group_layout"),
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&cube.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&cube.sampler),
+                },
+            ],
+            label: Some("skybox_bind_group"),
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Skybox Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout, camera_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = Self::build_pipeline(
+            device,
+            &layout,
+            color_format,
+            depth_format,
+            sample_count,
+            &shader.source,
+        )
+        .unwrap();
+
+        Self {
+            cube,
+            bind_group,
+            layout,
+            color_format,
+            depth_format,
+            sample_count,
+            pipeline,
+        }
+    }
+
+    // A pipeline for this skybox built from other (preprocessed) skybox_shader.wgsl
+    // source. It's only used once it's passed to set_pipeline.
+    pub fn create_pipeline(
+        &self,
+        device: &wgpu::Device,
+        source: &str,
+    ) -> anyhow::Result<wgpu::RenderPipeline> {
+        Self::build_pipeline(
+            device,
+            &self.layout,
+            self.color_format,
+            self.depth_format,
+            self.sample_count,
+            source,
+        )
+    }
+
+    // For passes with a different sample count. Pipelines made with create_pipeline
+    // from then on use it, the current one has to be replaced with one of those.
+    pub fn set_sample_count(&mut self, sample_count: u32) {
+        self.sample_count = sample_count;
+    }
+
+    pub fn set_pipeline(&mut self, pipeline: wgpu::RenderPipeline) {
+        self.pipeline = pipeline;
+    }
+
+    fn build_pipeline(
+        device: &wgpu::Device,
+        layout: &wgpu::PipelineLayout,
+        color_format: wgpu::TextureFormat,
+        depth_format: wgpu::TextureFormat,
+        sample_count: u32,
+        source: &str,
+    ) -> anyhow::Result<wgpu::RenderPipeline> {
+        // The fullscreen triangle is made up in the vertex shader, so there are no
+        // vertex buffers
+        PipelineBuilder::new(SKYBOX_SHADER, source)
+            .layout(layout)
+            .color_target(color_format)
+            .cull_mode(None)
+            // The skybox is drawn first and everything else goes over it, so it
+            // never needs testing against the depth buffer or writing to it. That
+            // also keeps it working whichever way round the depth range is.
+            .depth_stencil(wgpu::DepthStencilState {
+                format: depth_format,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            })
+            .sample_count(sample_count)
+            .build(device)
+    }
+
+    pub fn cube(&self) -> &CubeTexture {
+        &self.cube
+    }
+
+    // Has to come before anything else in the pass, since it covers the whole screen.
+    // Leaves bind groups 0 and 1 set to the skybox's own.
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass, camera_bind_group: &wgpu::BindGroup) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_bind_group(1, camera_bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,1646 @@
+use std::{ops::Range, time::Duration};
+
+use wgpu::{
+    BindGroupLayout, Color, Device, PipelineLayout, RenderPipeline, SurfaceConfiguration,
+    util::DeviceExt,
+};
+
+use winit::{
+    event::{DeviceEvent, ElementState, KeyEvent, WindowEvent},
+    keyboard::{KeyCode, PhysicalKey},
+    window::{CursorGrabMode, Window},
+};
+
+use crate::types::texture;
+
+use super::frame_capture::{self, FrameCapture};
+use super::render_target::{OffscreenTarget, RenderTarget};
+
+use super::{
+    camera_types::{
+        camera::{Camera, Projection},
+        camera_controller::CameraController,
+        camera_uniform::CameraUniform,
+        fps_camera::{FpsCamera, FpsController},
+        orbit_controller::OrbitController,
+    },
+    culling::{Aabb, BoundingSphere, CullStats, Frustum},
+    examples::{self, Example, ExampleContext, ExampleInfo},
+    gltf_scene::{DrawGltfScene, GltfScene},
+    instance_buffer::InstanceBuffer,
+    light_types::{
+        light::Light,
+        light_manager::{LIGHTS_INCLUDE, LightManager},
+        shadow_map::{ShadowMap, ShadowSettings},
+    },
+    model::{Material, white_texture},
+    pipeline_builder::PipelineBuilder,
+    polygon_buffer::PolygonBuffer,
+    preprocessor::{Preprocessor, ProcessedShader},
+    reflection,
+    shader_library::{
+        GLTF_SHADER, INSTANCED_SHADER, LIGHT_SHADER, LIT_SHADER, SHADOW_SHADER, SKYBOX_SHADER,
+        ShaderLibrary,
+    },
+    skybox::Skybox,
+    vertex_types::{
+        MeshVertex, Vertex,
+        instance::{Instance, InstanceRaw},
+        model_vertex::ModelVertex,
+        pbr_vertex::PbrVertex,
+        textured_vertex::*,
+    },
+};
+
+// The pipelines that are built from a shader in the ShaderLibrary
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ShaderPipeline {
+    Textured,
+    Lit,
+    Light,
+    Shadow,
+    Skybox,
+    Gltf,
+    // Shadow is for ModelVertex meshes, the others cast shadows from the vertex
+    // types that have the same name
+    PolygonShadow,
+    GltfShadow,
+}
+
+impl ShaderPipeline {
+    const ALL: [Self; 8] = [
+        Self::Textured,
+        Self::Lit,
+        Self::Light,
+        Self::Shadow,
+        Self::Skybox,
+        Self::Gltf,
+        Self::PolygonShadow,
+        Self::GltfShadow,
+    ];
+    const SHADOWS: [Self; 3] = [Self::Shadow, Self::PolygonShadow, Self::GltfShadow];
+
+    fn file(self) -> &'static str {
+        match self {
+            ShaderPipeline::Textured => INSTANCED_SHADER,
+            ShaderPipeline::Lit => LIT_SHADER,
+            ShaderPipeline::Light => LIGHT_SHADER,
+            ShaderPipeline::Shadow | ShaderPipeline::PolygonShadow | ShaderPipeline::GltfShadow => {
+                SHADOW_SHADER
+            }
+            ShaderPipeline::Skybox => SKYBOX_SHADER,
+            ShaderPipeline::Gltf => GLTF_SHADER,
+        }
+    }
+}
+
+// How State::new sets up the GPU and the window's surface
+pub struct RenderOptions {
+    // Which graphics APIs wgpu may pick an adapter from
+    pub backends: wgpu::Backends,
+    pub power_preference: wgpu::PowerPreference,
+    // None takes the first mode the surface offers. One it doesn't offer falls
+    // back to Fifo, which every surface has.
+    pub present_mode: Option<wgpu::PresentMode>,
+    // MSAA for the scene, 1 turns it off. The examples always draw without it.
+    pub sample_count: u32,
+    // Lights go in a storage buffer where the adapter can read one from vertex
+    // shaders. False always uses the uniform array WebGL2 falls back to.
+    pub storage_buffer_lights: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            // Backends::PRIMARY => Vulkan + Metal + DX12 + Browser WebGPU
+            #[cfg(not(target_arch = "wasm32"))]
+            backends: wgpu::Backends::PRIMARY,
+            #[cfg(target_arch = "wasm32")]
+            backends: wgpu::Backends::GL,
+            power_preference: wgpu::PowerPreference::default(),
+            present_mode: None,
+            sample_count: 1,
+            storage_buffer_lights: true,
+        }
+    }
+}
+
+pub struct State<'a> {
+    target: RenderTarget<'a>,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    config: wgpu::SurfaceConfiguration,
+    pub size: winit::dpi::PhysicalSize<u32>,
+    clear_color: wgpu::Color,
+    render_pipeline_layout: wgpu::PipelineLayout,
+    render_pipeline: wgpu::RenderPipeline,
+    lit_pipeline_layout: wgpu::PipelineLayout,
+    lit_render_pipeline: wgpu::RenderPipeline,
+    light_pipeline_layout: wgpu::PipelineLayout,
+    light_render_pipeline: wgpu::RenderPipeline,
+    shadow_pipeline_layout: wgpu::PipelineLayout,
+    shadow_render_pipeline: wgpu::RenderPipeline,
+    polygon_shadow_render_pipeline: wgpu::RenderPipeline,
+    gltf_shadow_render_pipeline: wgpu::RenderPipeline,
+    gltf_material_bind_group_layout: wgpu::BindGroupLayout,
+    gltf_pipeline_layout: wgpu::PipelineLayout,
+    gltf_render_pipeline: wgpu::RenderPipeline,
+    depth_texture: texture::DepthTexture,
+    depth_compare: wgpu::CompareFunction,
+    sample_count: u32,
+    // What the adapter can multisample both the surface and depth formats with
+    sample_counts: Vec<u32>,
+    // The scene is drawn into this and resolved into the frame when MSAA is on
+    msaa_texture: Option<wgpu::TextureView>,
+    polygon_buffer: PolygonBuffer<TexturedVertex>,
+    instance_buffer: InstanceBuffer,
+    cube_buffer: PolygonBuffer<ModelVertex>,
+    cube_instance_buffer: InstanceBuffer,
+    floor_instance_buffer: InstanceBuffer,
+    floor_material: Material,
+    gltf_scenes: Vec<GltfScene>,
+    diffuse_bind_group: wgpu::BindGroup,
+    _diffuse_texture: texture::Texture,
+    camera: Camera,
+    camera_uniform: CameraUniform,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group_layout: wgpu::BindGroupLayout,
+    camera_bind_group: wgpu::BindGroup,
+    camera_controller: CameraController,
+    orbit_controller: OrbitController,
+    // Set while flying around, the camera follows it instead of the other controllers
+    fps_camera: Option<FpsCamera>,
+    fps_controller: FpsController,
+    lights: LightManager,
+    shadow_map: ShadowMap,
+    frustum_culling: bool,
+    cull_stats: CullStats,
+    skybox: Option<Skybox>,
+    shaders: ShaderLibrary,
+    shadows: bool,
+    // The tutorial stage being shown instead of the scene, see examples::EXAMPLES
+    example: Option<Box<dyn Example>>,
+    example_index: usize,
+}
+
+impl<'a> State<'a> {
+    // Creating some of the wgpu types requires async code
+    pub async fn new(window: &'a Window, options: &RenderOptions) -> State<'a> {
+        let size = window.inner_size();
+
+        // The instance is a handle to our GPU
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: options.backends,
+            ..Default::default()
+        });
+
+        let surface = instance.create_surface(window).unwrap();
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: options.power_preference,
+                compatible_surface: Some(&surface),
+                force_fallback_adapter: false,
+            })
+            .await
+            .unwrap();
+
+        let (device, queue) = Self::request_device(&adapter).await.unwrap();
+
+        let surface_caps = surface.get_capabilities(&adapter);
+        // Shader code in this tutorial assumes an sRGB surface texture. Using a different
+        // one will result in all the colors coming out darker. If you want to support non
+        // sRGB surfaces, you'll need to account for that when drawing to the frame.
+        let surface_format = surface_caps
+            .formats
+            .iter()
+            .copied()
+            .find(|f| f.is_srgb())
+            .unwrap_or(surface_caps.formats[0]);
+
+        // COPY_SRC lets frames be captured as screenshots, if the surface allows it
+        let usage = wgpu::TextureUsages::RENDER_ATTACHMENT
+            | (surface_caps.usages & wgpu::TextureUsages::COPY_SRC);
+
+        // The Auto modes are always there, wgpu picks one of the others for them
+        let present_mode = match options.present_mode {
+            None => surface_caps.present_modes[0],
+            Some(mode @ (wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync)) => mode,
+            Some(mode) if surface_caps.present_modes.contains(&mode) => mode,
+            Some(mode) => {
+                log::warn!(
+                    "The surface can't present with {mode:?}, using Fifo. It can do {:?}",
+                    surface_caps.present_modes
+                );
+                wgpu::PresentMode::Fifo
+            }
+        };
+
+        let config = wgpu::SurfaceConfiguration {
+            usage,
+            format: surface_format,
+            width: size.width,
+            height: size.height,
+            present_mode,
+            alpha_mode: surface_caps.alpha_modes[0],
+            desired_maximum_frame_latency: 2,
+            view_formats: vec![],
+        };
+
+        // surface.configure(&device, &config);
+
+        Self::from_parts(
+            RenderTarget::Surface { surface, window },
+            &adapter,
+            device,
+            queue,
+            config,
+            options,
+        )
+    }
+
+    // Builds a State with no window that renders into an offscreen texture of the
+    // given size and format. Useful for CI and batch jobs. A software (fallback)
+    // adapter is preferred so output is the same across machines; set WGPU_BACKEND
+    // to pick the backend.
+    pub async fn new_headless(
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> anyhow::Result<State<'static>> {
+        Self::new_headless_with(width, height, format, &RenderOptions::default()).await
+    }
+
+    // new_headless with MSAA and the kind of light buffer taken from `options`. The
+    // adapter is picked as above, so the backends and power preference don't
+    // apply, and neither does the present mode.
+    pub async fn new_headless_with(
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        options: &RenderOptions,
+    ) -> anyhow::Result<State<'static>> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::from_env().unwrap_or(wgpu::Backends::all()),
+            ..Default::default()
+        });
+
+        let mut adapter = None;
+        for force_fallback_adapter in [true, false] {
+            adapter = instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::default(),
+                    compatible_surface: None,
+                    force_fallback_adapter,
+                })
+                .await;
+
+            if adapter.is_some() {
+                break;
+            }
+        }
+        let adapter = adapter
+            .ok_or_else(|| anyhow::anyhow!("no suitable adapter found for headless rendering"))?;
+
+        let (device, queue) = Self::request_device(&adapter).await?;
+
+        // Not used to configure a surface, but it carries the size and format the
+        // pipelines are built against, just like the windowed path.
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            format,
+            width,
+            height,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            desired_maximum_frame_latency: 2,
+            view_formats: vec![],
+        };
+
+        let target = RenderTarget::Offscreen(OffscreenTarget::new(&device, &config));
+
+        Ok(State::from_parts(
+            target, &adapter, device, queue, config, options,
+        ))
+    }
+
+    async fn request_device(
+        adapter: &wgpu::Adapter,
+    ) -> Result<(wgpu::Device, wgpu::Queue), wgpu::RequestDeviceError> {
+        adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    // Without it only 1 and 4 samples can be used for MSAA, whatever
+                    // the adapter can really do
+                    required_features: adapter.features()
+                        & wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES,
+                    // WebGL doesn't support all of wgpu's features, so if
+                    // we're building for the web, we'll have to disable some.
+                    required_limits: if cfg!(target_arch = "wasm32") {
+                        wgpu::Limits::downlevel_webgl2_defaults()
+                    } else {
+                        wgpu::Limits::default()
+                    },
+                    label: None,
+                    memory_hints: Default::default(),
+                },
+                None, // Trace path
+            )
+            .await
+    }
+
+    // Everything past device creation is shared between the windowed and headless paths.
+    fn from_parts(
+        target: RenderTarget<'a>,
+        adapter: &wgpu::Adapter,
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        config: wgpu::SurfaceConfiguration,
+        options: &RenderOptions,
+    ) -> State<'a> {
+        let size = winit::dpi::PhysicalSize::new(config.width, config.height);
+
+        let sample_counts = [1, 2, 4, 8, 16]
+            .into_iter()
+            .filter(|&count| {
+                [config.format, texture::DepthTexture::DEPTH_FORMAT]
+                    .iter()
+                    .all(|&format| {
+                        adapter
+                            .get_texture_format_features(format)
+                            .flags
+                            .sample_count_supported(count)
+                    })
+            })
+            .collect::<Vec<_>>();
+        let sample_count = if sample_counts.contains(&options.sample_count) {
+            options.sample_count
+        } else {
+            log::warn!(
+                "MSAA with {} samples isn't supported here, turning it off. The adapter can do {sample_counts:?}",
+                options.sample_count
+            );
+            1
+        };
+
+        // Storage buffers can only hold the lights if the gizmo's vertex shader can read
+        // them too, which WebGL2 (and some GL drivers) can't do.
+        let vertex_storage = options.storage_buffer_lights
+            && adapter
+                .get_downlevel_capabilities()
+                .flags
+                .contains(wgpu::DownlevelFlags::VERTEX_STORAGE);
+        let lights = LightManager::new(
+            &device,
+            &queue,
+            &[
+                Light::point((-0.5, 0.6, 0.0).into(), [1.0, 1.0, 1.0], 1.5, 4.0),
+                // A sun shining down at an angle, this one casts the shadows
+                Light::directional((0.5, -1.0, 0.6).into(), [1.0, 0.95, 0.85], 0.6),
+            ],
+            vertex_storage,
+        );
+
+        let shadow_map =
+            ShadowMap::new(&device, &queue, ShadowSettings::default(), lights.lights());
+
+        // Built into the binary, so they're known to work
+        let shaders = ShaderLibrary::embedded();
+        let reflect = |pipeline| {
+            Self::pipeline_shader(&shaders, &lights, pipeline, true)
+                .unwrap()
+                .reflect()
+                .unwrap()
+        };
+        let (textured, lit, light, skybox, gltf) = (
+            reflect(ShaderPipeline::Textured),
+            reflect(ShaderPipeline::Lit),
+            reflect(ShaderPipeline::Light),
+            reflect(ShaderPipeline::Skybox),
+            reflect(ShaderPipeline::Gltf),
+        );
+        // The layouts the shaders share are worked out from what each of them declares
+        let shared_layout =
+            |label, entries: Vec<anyhow::Result<Vec<wgpu::BindGroupLayoutEntry>>>| {
+                let entries =
+                    reflection::merge_entries(entries.into_iter().map(Result::unwrap)).unwrap();
+                device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    entries: &entries,
+                    label: Some(label),
+                })
+            };
+
+        // Every material is bound with this layout, so the texture and sampler
+        // entries follow from the options the textures are made with. Merging them
+        // with what the shaders declare checks the two agree. Materials all need to
+        // be filterable (anything but Filtering::Nearest) to share it.
+        let texture_options = texture::TextureOptions::TRILINEAR;
+        let texture_bind_group_layout = shared_layout(
+            "texture_bind_group_layout",
+            vec![
+                Ok(texture_options
+                    .layout_entries(wgpu::ShaderStages::FRAGMENT)
+                    .to_vec()),
+                textured.bind_group_layout_entries(0),
+                lit.bind_group_layout_entries(0),
+            ],
+        );
+        // glTF materials have their factors between the base color and emissive textures
+        let gltf_material_bind_group_layout = shared_layout(
+            "gltf_material_bind_group_layout",
+            vec![gltf.bind_group_layout_entries(0)],
+        );
+
+        let diffuse_bytes = include_bytes!("resources/challenge_image.jpeg");
+        let (diffuse_bind_group, diffuse_texture) = Self::generate_texture(
+            diffuse_bytes,
+            "resources/challenge_image.jpeg",
+            texture_options,
+            &texture_bind_group_layout,
+            &device,
+            &queue,
+        );
+
+        let camera = Camera {
+            eye: (0.0, 1.0, 2.0).into(),
+            target: (0.0, 0.0, 0.0).into(),
+            up: cgmath::Vector3::unit_y(),
+            aspect: config.width as f32 / config.height as f32,
+            projection: Projection::Perspective {
+                fovy: cgmath::Deg(45.0),
+                znear: 0.1,
+                zfar: 100.0,
+            },
+            reverse_z: false,
+        };
+
+        let mut camera_uniform = CameraUniform::new();
+        camera_uniform.update_view_proj(&camera);
+
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Camera Buffer"),
+            contents: bytemuck::cast_slice(&[camera_uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        // The light gizmos have the camera at group 0, since they've no texture
+        let camera_bind_group_layout = shared_layout(
+            "camera_bind_group_layout",
+            vec![
+                textured.bind_group_layout_entries(1),
+                lit.bind_group_layout_entries(1),
+                skybox.bind_group_layout_entries(1),
+                gltf.bind_group_layout_entries(1),
+                light.bind_group_layout_entries(0),
+            ],
+        );
+
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &camera_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+            label: Some("camera_bind_group"),
+        });
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Render Pipeline Layout"),
+                bind_group_layouts: &[&texture_bind_group_layout, &camera_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let lit_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Lit Pipeline Layout"),
+            bind_group_layouts: &[
+                &texture_bind_group_layout,
+                &camera_bind_group_layout,
+                lights.bind_group_layout(),
+                shadow_map.bind_group_layout(),
+            ],
+            push_constant_ranges: &[],
+        });
+
+        let gltf_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("glTF Pipeline Layout"),
+            bind_group_layouts: &[
+                &gltf_material_bind_group_layout,
+                &camera_bind_group_layout,
+                lights.bind_group_layout(),
+                shadow_map.bind_group_layout(),
+            ],
+            push_constant_ranges: &[],
+        });
+
+        let light_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Light Pipeline Layout"),
+                bind_group_layouts: &[&camera_bind_group_layout, lights.bind_group_layout()],
+                push_constant_ranges: &[],
+            });
+
+        let shadow_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Shadow Pipeline Layout"),
+                bind_group_layouts: &[shadow_map.pass_bind_group_layout()],
+                push_constant_ranges: &[],
+            });
+
+        let depth_texture =
+            texture::DepthTexture::create_depth_texture(&device, &config, sample_count);
+        let msaa_texture = Self::create_msaa_texture(&device, &config, sample_count);
+        let depth_compare = wgpu::CompareFunction::Less;
+
+        let shader = |pipeline| {
+            Self::pipeline_shader(&shaders, &lights, pipeline, true)
+                .unwrap()
+                .source
+        };
+        let render_pipeline = Self::textured_pipeline(
+            &render_pipeline_layout,
+            &device,
+            &config,
+            depth_compare,
+            sample_count,
+            &shader(ShaderPipeline::Textured),
+        )
+        .unwrap();
+        let lit_render_pipeline = Self::lit_pipeline(
+            &lit_pipeline_layout,
+            &device,
+            &config,
+            depth_compare,
+            sample_count,
+            &shader(ShaderPipeline::Lit),
+        )
+        .unwrap();
+        let light_render_pipeline = Self::light_pipeline(
+            &light_pipeline_layout,
+            &device,
+            &config,
+            depth_compare,
+            sample_count,
+            &shader(ShaderPipeline::Light),
+        )
+        .unwrap();
+        let shadow_render_pipeline = Self::shadow_pipeline::<ModelVertex>(
+            &shadow_pipeline_layout,
+            &device,
+            &shadow_map,
+            &shader(ShaderPipeline::Shadow),
+        )
+        .unwrap();
+        let polygon_shadow_render_pipeline = Self::shadow_pipeline::<TexturedVertex>(
+            &shadow_pipeline_layout,
+            &device,
+            &shadow_map,
+            &shader(ShaderPipeline::PolygonShadow),
+        )
+        .unwrap();
+        let gltf_shadow_render_pipeline = Self::shadow_pipeline::<PbrVertex>(
+            &shadow_pipeline_layout,
+            &device,
+            &shadow_map,
+            &shader(ShaderPipeline::GltfShadow),
+        )
+        .unwrap();
+        let gltf_render_pipeline = Self::gltf_pipeline(
+            &gltf_pipeline_layout,
+            &device,
+            &config,
+            depth_compare,
+            sample_count,
+            &shader(ShaderPipeline::Gltf),
+        )
+        .unwrap();
+
+        let camera_controller = CameraController::new(4.0);
+        let orbit_controller = OrbitController::new(0.5, 20.0);
+        let fps_controller = FpsController::new(3.0, 0.003);
+
+        let polygon_buffer = PolygonBuffer::new(&device, VERTICES, INDICES);
+        // A single untransformed copy until set_instances is called
+        let instance_buffer = InstanceBuffer::new(&device, &[Instance::default()]);
+
+        // A lit cube behind the polygon, to show off the lights
+        let (cube_vertices, cube_indices) = ModelVertex::cube(0.5);
+        let cube_buffer = PolygonBuffer::new(&device, &cube_vertices, &cube_indices);
+        let cube_instance_buffer = InstanceBuffer::new(
+            &device,
+            &[Instance {
+                position: cgmath::Vector3::new(0.9, 0.0, -1.0),
+                rotation: cgmath::Rotation3::from_angle_y(cgmath::Deg(30.0)),
+                ..Default::default()
+            }],
+        );
+
+        // A plain white slab under the cube for it to cast a shadow on
+        let floor_instance_buffer = InstanceBuffer::new(
+            &device,
+            &[Instance {
+                position: cgmath::Vector3::new(0.3, -0.8, -1.0),
+                scale: cgmath::Vector3::new(3.0, 0.1, 3.0),
+                ..Default::default()
+            }],
+        );
+        let floor_material = Material::new(
+            &device,
+            "floor".to_string(),
+            white_texture(&device, &queue).unwrap(),
+            &texture_bind_group_layout,
+        );
+
+        Self {
+            target,
+            device,
+            queue,
+            config,
+            size,
+            clear_color: Color {
+                r: 0.0,
+                g: 0.5,
+                b: 0.5,
+                a: 1.0,
+            },
+            render_pipeline_layout,
+            render_pipeline,
+            lit_pipeline_layout,
+            lit_render_pipeline,
+            light_pipeline_layout,
+            light_render_pipeline,
+            shadow_pipeline_layout,
+            shadow_render_pipeline,
+            polygon_shadow_render_pipeline,
+            gltf_shadow_render_pipeline,
+            gltf_material_bind_group_layout,
+            gltf_pipeline_layout,
+            gltf_render_pipeline,
+            depth_texture,
+            depth_compare,
+            sample_count,
+            sample_counts,
+            msaa_texture,
+            polygon_buffer,
+            instance_buffer,
+            cube_buffer,
+            cube_instance_buffer,
+            floor_instance_buffer,
+            floor_material,
+            gltf_scenes: Vec::new(),
+            diffuse_bind_group,
+            _diffuse_texture: diffuse_texture,
+            camera,
+            camera_uniform,
+            camera_buffer,
+            camera_bind_group_layout,
+            camera_bind_group,
+            camera_controller,
+            orbit_controller,
+            fps_camera: None,
+            fps_controller,
+            lights,
+            shadow_map,
+            frustum_culling: true,
+            cull_stats: CullStats::default(),
+            skybox: None,
+            shaders,
+            shadows: true,
+            example: None,
+            example_index: examples::EXAMPLES
+                .iter()
+                .position(ExampleInfo::is_scene)
+                .unwrap(),
+        }
+    }
+
+    fn generate_texture(
+        diffuse_bytes: &[u8],
+        label: &str,
+        options: texture::TextureOptions,
+        texture_bind_group_layout: &BindGroupLayout,
+        device: &Device,
+        queue: &wgpu::Queue,
+    ) -> (wgpu::BindGroup, texture::Texture) {
+        let diffuse_texture =
+            texture::Texture::from_bytes(device, queue, diffuse_bytes, label, options).unwrap();
+
+        let diffuse_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: texture_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&diffuse_texture.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&diffuse_texture.sampler),
+                },
+            ],
+            label: Some("diffuse_bind_group"),
+        });
+
+        (diffuse_bind_group, diffuse_texture)
+    }
+
+    fn textured_pipeline(
+        layout: &PipelineLayout,
+        device: &Device,
+        config: &SurfaceConfiguration,
+        depth_compare: wgpu::CompareFunction,
+        sample_count: u32,
+        source: &str,
+    ) -> anyhow::Result<RenderPipeline> {
+        PipelineBuilder::new(INSTANCED_SHADER, source)
+            .layout(layout)
+            .vertex_buffers([TexturedVertex::desc(), InstanceRaw::desc()])
+            .color_target(config.format)
+            .depth_stencil(texture::DepthTexture::depth_stencil_state(depth_compare))
+            .sample_count(sample_count)
+            .build(device)
+    }
+
+    fn lit_pipeline(
+        layout: &PipelineLayout,
+        device: &Device,
+        config: &SurfaceConfiguration,
+        depth_compare: wgpu::CompareFunction,
+        sample_count: u32,
+        source: &str,
+    ) -> anyhow::Result<RenderPipeline> {
+        PipelineBuilder::new(LIT_SHADER, source)
+            .layout(layout)
+            .vertex_buffers([ModelVertex::desc(), InstanceRaw::desc()])
+            .color_target(config.format)
+            .depth_stencil(texture::DepthTexture::depth_stencil_state(depth_compare))
+            .sample_count(sample_count)
+            .build(device)
+    }
+
+    // Lit like the cube, but with the vertices and materials of a glTF file
+    fn gltf_pipeline(
+        layout: &PipelineLayout,
+        device: &Device,
+        config: &SurfaceConfiguration,
+        depth_compare: wgpu::CompareFunction,
+        sample_count: u32,
+        source: &str,
+    ) -> anyhow::Result<RenderPipeline> {
+        PipelineBuilder::new(GLTF_SHADER, source)
+            .layout(layout)
+            .vertex_buffers([PbrVertex::desc(), InstanceRaw::desc()])
+            .color_target(config.format)
+            .depth_stencil(texture::DepthTexture::depth_stencil_state(depth_compare))
+            .sample_count(sample_count)
+            .build(device)
+    }
+
+    // Unlit, just draws each light's position so we can see where they are
+    fn light_pipeline(
+        layout: &PipelineLayout,
+        device: &Device,
+        config: &SurfaceConfiguration,
+        depth_compare: wgpu::CompareFunction,
+        sample_count: u32,
+        source: &str,
+    ) -> anyhow::Result<RenderPipeline> {
+        PipelineBuilder::new(LIGHT_SHADER, source)
+            .layout(layout)
+            .vertex_buffer(ModelVertex::desc())
+            .color_target(config.format)
+            .depth_stencil(texture::DepthTexture::depth_stencil_state(depth_compare))
+            .sample_count(sample_count)
+            .build(device)
+    }
+
+    // Depth only, with the shadow map's bias. Nothing is culled so that open meshes
+    // and single sided polygons still cast shadows. Only the position is read, so
+    // the same shader works for any vertex type V.
+    fn shadow_pipeline<V: MeshVertex>(
+        layout: &PipelineLayout,
+        device: &Device,
+        shadow_map: &ShadowMap,
+        source: &str,
+    ) -> anyhow::Result<RenderPipeline> {
+        PipelineBuilder::new(SHADOW_SHADER, source)
+            .layout(layout)
+            .vertex_buffers([V::position_desc(), InstanceRaw::desc()])
+            .no_fragment()
+            .cull_mode(None)
+            .depth_stencil(shadow_map.depth_stencil_state())
+            .build(device)
+    }
+
+    // None when running headless.
+    pub fn window(&self) -> Option<&Window> {
+        self.target.window()
+    }
+
+    pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        if new_size.width > 0 && new_size.height > 0 {
+            self.size = new_size;
+            self.config.width = new_size.width;
+            self.config.height = new_size.height;
+            self.target.configure(&self.device, &self.config);
+            self.depth_texture = texture::DepthTexture::create_depth_texture(
+                &self.device,
+                &self.config,
+                self.sample_count,
+            );
+            self.msaa_texture =
+                Self::create_msaa_texture(&self.device, &self.config, self.sample_count);
+
+            self.camera.aspect = self.config.width as f32 / self.config.height as f32;
+            if let Some(example) = &mut self.example {
+                example.resize(&ExampleContext {
+                    device: &self.device,
+                    queue: &self.queue,
+                    config: &self.config,
+                });
+            }
+        }
+    }
+
+    // Turns MSAA for the scene on with 2 or more samples, or off with 1. Only the
+    // counts in sample_counts() work here.
+    pub fn set_sample_count(&mut self, sample_count: u32) -> anyhow::Result<()> {
+        if !self.sample_counts.contains(&sample_count) {
+            anyhow::bail!(
+                "MSAA with {sample_count} samples isn't supported here, the adapter can do {:?}",
+                self.sample_counts
+            );
+        }
+
+        self.sample_count = sample_count;
+        self.depth_texture =
+            texture::DepthTexture::create_depth_texture(&self.device, &self.config, sample_count);
+        self.msaa_texture = Self::create_msaa_texture(&self.device, &self.config, sample_count);
+        if let Some(skybox) = &mut self.skybox {
+            skybox.set_sample_count(sample_count);
+        }
+        for pipeline in [
+            ShaderPipeline::Textured,
+            ShaderPipeline::Lit,
+            ShaderPipeline::Light,
+            ShaderPipeline::Skybox,
+            ShaderPipeline::Gltf,
+        ] {
+            self.rebuild_pipeline(pipeline);
+        }
+        Ok(())
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    pub fn sample_counts(&self) -> &[u32] {
+        &self.sample_counts
+    }
+
+    // Only needed with MSAA, otherwise the scene goes straight into the frame
+    fn create_msaa_texture(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        sample_count: u32,
+    ) -> Option<wgpu::TextureView> {
+        if sample_count == 1 {
+            return None;
+        }
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("MSAA Texture"),
+            size: wgpu::Extent3d {
+                width: config.width.max(1),
+                height: config.height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        Some(texture.create_view(&wgpu::TextureViewDescriptor::default()))
+    }
+
+    // Rebuilds the render pipelines so fragments are depth tested with the given function.
+    pub fn set_depth_compare(&mut self, depth_compare: wgpu::CompareFunction) {
+        self.depth_compare = depth_compare;
+        for pipeline in [
+            ShaderPipeline::Textured,
+            ShaderPipeline::Lit,
+            ShaderPipeline::Light,
+            ShaderPipeline::Gltf,
+        ] {
+            self.rebuild_pipeline(pipeline);
+        }
+    }
+
+    // Rebuilds every pipeline from `shaders`. With ShaderLibrary::from_dir, each
+    // shader's pipelines are rebuilt again whenever its file is saved. A shader
+    // that doesn't compile is logged and the last pipeline that worked is kept.
+    pub fn set_shader_library(&mut self, shaders: ShaderLibrary) {
+        self.shaders = shaders;
+        for pipeline in ShaderPipeline::ALL {
+            self.rebuild_pipeline(pipeline);
+        }
+    }
+
+    fn rebuild_changed_shaders(&mut self) {
+        for file in self.shaders.poll_changes() {
+            log::info!("{file} changed, rebuilding its pipelines");
+            // Files that are only #included could be in any of them
+            let is_included = ShaderPipeline::ALL.iter().all(|p| p.file() != file);
+            for pipeline in ShaderPipeline::ALL
+                .into_iter()
+                .filter(|p| is_included || p.file() == file)
+            {
+                self.rebuild_pipeline(pipeline);
+            }
+        }
+    }
+
+    fn rebuild_pipeline(&mut self, pipeline: ShaderPipeline) {
+        if pipeline == ShaderPipeline::Skybox && self.skybox.is_none() {
+            return;
+        }
+
+        let built = match self.build_pipeline(pipeline) {
+            Ok(built) => built,
+            Err(e) => {
+                log::error!(
+                    "Couldn't build the {pipeline:?} pipeline, keeping the last one that worked: {e:#}"
+                );
+                return;
+            }
+        };
+        match pipeline {
+            ShaderPipeline::Textured => self.render_pipeline = built,
+            ShaderPipeline::Lit => self.lit_render_pipeline = built,
+            ShaderPipeline::Light => self.light_render_pipeline = built,
+            ShaderPipeline::Shadow => self.shadow_render_pipeline = built,
+            ShaderPipeline::Gltf => self.gltf_render_pipeline = built,
+            ShaderPipeline::PolygonShadow => self.polygon_shadow_render_pipeline = built,
+            ShaderPipeline::GltfShadow => self.gltf_shadow_render_pipeline = built,
+            ShaderPipeline::Skybox => {
+                if let Some(skybox) = &mut self.skybox {
+                    skybox.set_pipeline(built);
+                }
+            }
+        }
+    }
+
+    // The preprocessed source of one of the pipelines' shaders, with the features
+    // that are switched on #defined.
+    fn pipeline_shader(
+        shaders: &ShaderLibrary,
+        lights: &LightManager,
+        pipeline: ShaderPipeline,
+        shadows: bool,
+    ) -> anyhow::Result<ProcessedShader> {
+        let mut preprocessor = Preprocessor::new(|file| match file {
+            LIGHTS_INCLUDE => Ok(lights.include_source().into()),
+            _ => shaders.source(file),
+        });
+        if shadows {
+            preprocessor.define("SHADOWS", "");
+        }
+        preprocessor.process(pipeline.file())
+    }
+
+    // Shaders read from disk are checked before they're used, so a bad edit is an
+    // error here rather than a panic. The skybox pipeline needs a skybox.
+    fn build_pipeline(&self, pipeline: ShaderPipeline) -> anyhow::Result<RenderPipeline> {
+        let shader = Self::pipeline_shader(&self.shaders, &self.lights, pipeline, self.shadows)?;
+        let source = &shader.source;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.shaders.is_watching() {
+            shader.validate()?;
+            // naga doesn't know what this device can do, anything it misses turns
+            // up in the error scope instead of panicking
+            self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        }
+
+        // Not returned straight away, so the error scope is always popped
+        let built = match pipeline {
+            ShaderPipeline::Textured => Self::textured_pipeline(
+                &self.render_pipeline_layout,
+                &self.device,
+                &self.config,
+                self.depth_compare,
+                self.sample_count,
+                source,
+            ),
+            ShaderPipeline::Lit => Self::lit_pipeline(
+                &self.lit_pipeline_layout,
+                &self.device,
+                &self.config,
+                self.depth_compare,
+                self.sample_count,
+                source,
+            ),
+            ShaderPipeline::Light => Self::light_pipeline(
+                &self.light_pipeline_layout,
+                &self.device,
+                &self.config,
+                self.depth_compare,
+                self.sample_count,
+                source,
+            ),
+            ShaderPipeline::Shadow => Self::shadow_pipeline::<ModelVertex>(
+                &self.shadow_pipeline_layout,
+                &self.device,
+                &self.shadow_map,
+                source,
+            ),
+            ShaderPipeline::PolygonShadow => Self::shadow_pipeline::<TexturedVertex>(
+                &self.shadow_pipeline_layout,
+                &self.device,
+                &self.shadow_map,
+                source,
+            ),
+            ShaderPipeline::GltfShadow => Self::shadow_pipeline::<PbrVertex>(
+                &self.shadow_pipeline_layout,
+                &self.device,
+                &self.shadow_map,
+                source,
+            ),
+            ShaderPipeline::Skybox => self
+                .skybox
+                .as_ref()
+                .expect("the skybox pipeline needs a skybox")
+                .create_pipeline(&self.device, source),
+            ShaderPipeline::Gltf => Self::gltf_pipeline(
+                &self.gltf_pipeline_layout,
+                &self.device,
+                &self.config,
+                self.depth_compare,
+                self.sample_count,
+                source,
+            ),
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.shaders.is_watching()
+            && let Some(error) = pollster::block_on(self.device.pop_error_scope())
+        {
+            anyhow::bail!("{error}");
+        }
+
+        built
+    }
+
+    // Replaces every light in the scene. Without storage buffers (WebGL2) only the
+    // first MAX_UNIFORM_LIGHTS are used.
+    pub fn set_lights(&mut self, lights: &[Light]) {
+        self.lights.set_lights(&self.device, &self.queue, lights);
+        self.shadow_map.update(&self.queue, self.lights.lights());
+    }
+
+    // False when the lights are in the uniform array instead, which only holds
+    // MAX_UNIFORM_LIGHTS of them
+    pub fn storage_buffer_lights(&self) -> bool {
+        self.lights.uses_storage_buffer()
+    }
+
+    // The first directional light casts shadows, these control how they're rendered.
+    pub fn set_shadow_settings(&mut self, settings: ShadowSettings) {
+        self.shadow_map
+            .set_settings(&self.device, &self.queue, settings, self.lights.lights());
+        for pipeline in ShaderPipeline::SHADOWS {
+            self.rebuild_pipeline(pipeline);
+        }
+    }
+
+    // Turns shadows off (or back on). The lit shaders are rebuilt without them, so
+    // they're as fast as if they'd never been there.
+    pub fn set_shadows(&mut self, enabled: bool) {
+        self.shadows = enabled;
+        self.rebuild_pipeline(ShaderPipeline::Lit);
+        self.rebuild_pipeline(ShaderPipeline::Gltf);
+    }
+
+    // Every instance is a copy of the polygon, drawn with as few draw_indexed calls as
+    // culling allows.
+    pub fn set_instances(&mut self, instances: &[Instance]) {
+        self.instance_buffer
+            .update(&self.device, &self.queue, instances);
+    }
+
+    // Loads a .gltf or .glb file and draws it with the rest of the scene, where
+    // its nodes put it. Each mesh is culled like the rest of the scene, and drawn
+    // until clear_gltf_scenes.
+    pub fn add_gltf_scene(&mut self, path: impl AsRef<std::path::Path>) -> anyhow::Result<()> {
+        let scene = GltfScene::load(
+            &self.device,
+            &self.queue,
+            path,
+            &self.gltf_material_bind_group_layout,
+        )?;
+        self.gltf_scenes.push(scene);
+        Ok(())
+    }
+
+    pub fn clear_gltf_scenes(&mut self) {
+        self.gltf_scenes.clear();
+    }
+
+    // Takes effect on the next update. The aspect ratio comes from the window, so
+    // it stays right through resizes whichever projection is used. A projection
+    // that fails Projection::validate is turned down and the old one kept.
+    pub fn set_projection(&mut self, projection: Projection) -> anyhow::Result<()> {
+        projection.validate()?;
+        self.camera.projection = projection;
+        Ok(())
+    }
+
+    // Reverse-Z flips the depth range so the depth test has to flip with it.
+    pub fn set_reverse_z(&mut self, reverse_z: bool) {
+        self.camera.reverse_z = reverse_z;
+        self.set_depth_compare(if reverse_z {
+            wgpu::CompareFunction::Greater
+        } else {
+            wgpu::CompareFunction::Less
+        });
+    }
+
+    // Instances outside the camera's view are skipped when drawing. Turning this off
+    // is mostly useful to check the culling isn't throwing away anything it shouldn't.
+    pub fn set_frustum_culling(&mut self, enabled: bool) {
+        self.frustum_culling = enabled;
+    }
+
+    // How many instances the last frame drew and skipped, not counting the shadow
+    // pass (things out of view can still cast shadows into it) or the light gizmos.
+    pub fn cull_stats(&self) -> CullStats {
+        self.cull_stats
+    }
+
+    // The ranges of instances to draw of a mesh with these bounds
+    fn visible_instances(
+        &self,
+        frustum: &Frustum,
+        aabb: &Aabb,
+        sphere: &BoundingSphere,
+        instances: &InstanceBuffer,
+        stats: &mut CullStats,
+    ) -> Vec<Range<u32>> {
+        if !self.frustum_culling {
+            stats.drawn += instances.num_instances;
+            return std::iter::once(0..instances.num_instances).collect();
+        }
+        frustum.visible_instances(aabb, sphere, instances.instances(), stats)
+    }
+
+    // Six images in the order +X, -X, +Y, -Y, +Z, -Z, drawn behind everything
+    // instead of the clear color.
+    pub fn set_skybox_faces(&mut self, faces: &[image::DynamicImage; 6]) -> anyhow::Result<()> {
+        let cube = texture::CubeTexture::from_faces(
+            &self.device,
+            &self.queue,
+            faces,
+            Some("Skybox"),
+            texture::ColorSpace::Srgb,
+        )?;
+        self.set_skybox(cube);
+        Ok(())
+    }
+
+    // A 2:1 panorama, usually HDR, turned into a cubemap with `face_size` texels
+    // along each edge.
+    pub fn set_skybox_equirectangular(
+        &mut self,
+        img: &image::DynamicImage,
+        face_size: u32,
+    ) -> anyhow::Result<()> {
+        let cube = texture::CubeTexture::from_equirectangular(
+            &self.device,
+            &self.queue,
+            img,
+            face_size,
+            Some("Skybox"),
+        )?;
+        self.set_skybox(cube);
+        Ok(())
+    }
+
+    // Goes back to clearing the background to a flat color
+    pub fn remove_skybox(&mut self) {
+        self.skybox = None;
+    }
+
+    fn set_skybox(&mut self, cube: texture::CubeTexture) {
+        self.skybox = Some(Skybox::new(
+            &self.device,
+            cube,
+            &self.camera_bind_group_layout,
+            self.config.format,
+            texture::DepthTexture::DEPTH_FORMAT,
+            self.sample_count,
+        ));
+        // Skybox::new always starts out with the built in shader
+        if self.shaders.is_watching() {
+            self.rebuild_pipeline(ShaderPipeline::Skybox);
+        }
+    }
+
+    // The depth buffer is cleared to whatever counts as "furthest away" for the
+    // current compare function, so the first fragment drawn always passes.
+    fn depth_clear_value(&self) -> f32 {
+        match self.depth_compare {
+            wgpu::CompareFunction::Greater | wgpu::CompareFunction::GreaterEqual => 0.0,
+            _ => 1.0,
+        }
+    }
+
+    pub fn input(&mut self, event: &WindowEvent) -> bool {
+        // The examples have their own controls
+        if let Some(example) = &mut self.example {
+            return example.input(event);
+        }
+
+        match event {
+            // F switches between orbiting the scene and flying around it
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        state: ElementState::Pressed,
+                        physical_key: PhysicalKey::Code(KeyCode::KeyF),
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } => {
+                self.set_fly_mode(self.fps_camera.is_none());
+                return true;
+            }
+            // The cursor is released when the window loses focus (or the browser
+            // drops the pointer lock), so flying stops with it
+            WindowEvent::Focused(false) => self.set_fly_mode(false),
+            _ => {}
+        }
+
+        if self.fps_camera.is_some() {
+            self.fps_controller.process_events(event)
+        } else {
+            // The keyboard and the mouse can both move the camera
+            self.camera_controller.process_events(event)
+                || self.orbit_controller.process_events(event)
+        }
+    }
+
+    // Raw input that isn't tied to the window, only mouse motion is used (for flying).
+    pub fn device_input(&mut self, event: &DeviceEvent) -> bool {
+        match event {
+            DeviceEvent::MouseMotion { delta } if self.fps_camera.is_some() => {
+                self.fps_controller.process_mouse(delta.0, delta.1);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Flying grabs and hides the cursor, so the mouse can turn the camera as far
+    // as it likes without leaving the window.
+    pub fn set_fly_mode(&mut self, enabled: bool) {
+        if enabled == self.fps_camera.is_some() {
+            return;
+        }
+
+        self.fps_controller.reset();
+        self.fps_camera =
+            enabled.then(|| FpsCamera::looking_at(self.camera.eye, self.camera.target));
+
+        if let Some(window) = self.window() {
+            let grabbed = if enabled {
+                // Locked keeps the cursor in place (pointer lock on the web), but not
+                // every platform has it; Confined at least keeps it in the window
+                window
+                    .set_cursor_grab(CursorGrabMode::Locked)
+                    .or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined))
+            } else {
+                window.set_cursor_grab(CursorGrabMode::None)
+            };
+            if let Err(e) = grabbed {
+                log::warn!("Couldn't change the cursor grab: {e}");
+            }
+            window.set_cursor_visible(!enabled);
+        }
+    }
+
+    // `dt` is the time since the last update, anything that moves on its own is scaled by it.
+    pub fn update(&mut self, dt: Duration) {
+        self.rebuild_changed_shaders();
+
+        if let Some(example) = &mut self.example {
+            example.update(
+                &ExampleContext {
+                    device: &self.device,
+                    queue: &self.queue,
+                    config: &self.config,
+                },
+                dt,
+            );
+            return;
+        }
+
+        if let Some(fps_camera) = &mut self.fps_camera {
+            self.fps_controller.update_camera(fps_camera, dt);
+            fps_camera.apply_to(&mut self.camera);
+        } else {
+            self.camera_controller.update_camera(&mut self.camera, dt);
+            // Only moves as far as the mouse did, so it doesn't need the time
+            self.orbit_controller.update_camera(&mut self.camera);
+        }
+        self.camera_uniform.update_view_proj(&self.camera);
+        self.queue.write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::cast_slice(&[self.camera_uniform]),
+        );
+    }
+
+    // Shows one of examples::EXAMPLES in place of the scene, or the scene again.
+    // Examples start over each time they're picked.
+    pub fn set_example(&mut self, index: usize) {
+        let info = &examples::EXAMPLES[index];
+        log::info!("Showing the {} example: {}", info.name, info.description);
+        // The examples don't fly, and shouldn't be left with the cursor grabbed
+        self.set_fly_mode(false);
+        self.example = info.init(&ExampleContext {
+            device: &self.device,
+            queue: &self.queue,
+            config: &self.config,
+        });
+        self.example_index = index;
+    }
+
+    pub fn example(&self) -> &'static ExampleInfo {
+        &examples::EXAMPLES[self.example_index]
+    }
+
+    pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
+        self.render_frame(false).map(|_| ())
+    }
+
+    // Renders a frame and reads it back from the GPU. Blocks until the copy is done.
+    pub fn capture(&mut self) -> anyhow::Result<image::RgbaImage> {
+        if !self.config.usage.contains(wgpu::TextureUsages::COPY_SRC) {
+            anyhow::bail!("this render target does not support copying frames out of it");
+        }
+        // Checked before anything is drawn, not once the frame has been copied
+        frame_capture::swaps_red_blue(self.config.format)?;
+
+        let capture = self.render_frame(true)?.expect("a capture was requested");
+        capture.read(&self.device)
+    }
+
+    pub fn save_screenshot(&mut self, path: impl AsRef<std::path::Path>) -> anyhow::Result<()> {
+        self.capture()?.save(path)?;
+        Ok(())
+    }
+
+    fn render_frame(&mut self, capture: bool) -> Result<Option<FrameCapture>, wgpu::SurfaceError> {
+        let frame = self.target.acquire()?;
+
+        let view = frame
+            .texture()
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Encoder"),
+            });
+
+        match &mut self.example {
+            Some(example) => {
+                let context = ExampleContext {
+                    device: &self.device,
+                    queue: &self.queue,
+                    config: &self.config,
+                };
+                example.render(&context, &view, &mut encoder);
+            }
+            None => self.cull_stats = self.render_scene(&view, &mut encoder),
+        }
+
+        // The copy has to be recorded before the frame is presented, since a
+        // surface texture can't be touched afterwards.
+        let capture =
+            capture.then(|| FrameCapture::copy_from(&self.device, &mut encoder, frame.texture()));
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+        frame.present();
+
+        Ok(capture)
+    }
+
+    // Returns how many instances were culled on the way
+    fn render_scene(
+        &self,
+        view: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+    ) -> CullStats {
+        let frustum = Frustum::from_matrix(&self.camera.build_view_projection_matrix());
+        let mut cull_stats = CullStats::default();
+        let polygon_ranges = self.visible_instances(
+            &frustum,
+            &self.polygon_buffer.aabb,
+            &self.polygon_buffer.bounding_sphere,
+            &self.instance_buffer,
+            &mut cull_stats,
+        );
+        let cube_ranges = self.visible_instances(
+            &frustum,
+            &self.cube_buffer.aabb,
+            &self.cube_buffer.bounding_sphere,
+            &self.cube_instance_buffer,
+            &mut cull_stats,
+        );
+        let floor_ranges = self.visible_instances(
+            &frustum,
+            &self.cube_buffer.aabb,
+            &self.cube_buffer.bounding_sphere,
+            &self.floor_instance_buffer,
+            &mut cull_stats,
+        );
+        // For each scene, the ranges of each of its meshes
+        let mut gltf_ranges = Vec::with_capacity(self.gltf_scenes.len());
+        for scene in &self.gltf_scenes {
+            let mut ranges = Vec::new();
+            for (mesh, instances) in scene.mesh_instances() {
+                ranges.push(self.visible_instances(
+                    &frustum,
+                    &mesh.aabb,
+                    &mesh.bounding_sphere,
+                    instances,
+                    &mut cull_stats,
+                ));
+            }
+            gltf_ranges.push(ranges);
+        }
+
+        // The shadow map has to be finished before the color pass samples it
+        if self.shadows && self.shadow_map.is_active() {
+            let mut shadow_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Shadow Pass"),
+                color_attachments: &[],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: self.shadow_map.view(),
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+
+            // Everything that's drawn casts a shadow, each vertex type with its own pipeline
+            shadow_pass.set_pipeline(&self.polygon_shadow_render_pipeline);
+            shadow_pass.set_bind_group(0, self.shadow_map.pass_bind_group(), &[]);
+            shadow_pass.set_vertex_buffer(0, self.polygon_buffer.vertex_buffer.slice(..));
+            shadow_pass.set_vertex_buffer(1, self.instance_buffer.buffer.slice(..));
+            shadow_pass.set_index_buffer(
+                self.polygon_buffer.index_buffer.slice(..),
+                self.polygon_buffer.index_format,
+            );
+            shadow_pass.draw_indexed(
+                0..self.polygon_buffer.num_indices,
+                0,
+                0..self.instance_buffer.num_instances,
+            );
+
+            shadow_pass.set_pipeline(&self.shadow_render_pipeline);
+            shadow_pass.set_vertex_buffer(0, self.cube_buffer.vertex_buffer.slice(..));
+            shadow_pass.set_vertex_buffer(1, self.cube_instance_buffer.buffer.slice(..));
+            shadow_pass.set_index_buffer(
+                self.cube_buffer.index_buffer.slice(..),
+                self.cube_buffer.index_format,
+            );
+            shadow_pass.draw_indexed(
+                0..self.cube_buffer.num_indices,
+                0,
+                0..self.cube_instance_buffer.num_instances,
+            );
+            shadow_pass.set_vertex_buffer(1, self.floor_instance_buffer.buffer.slice(..));
+            shadow_pass.draw_indexed(
+                0..self.cube_buffer.num_indices,
+                0,
+                0..self.floor_instance_buffer.num_instances,
+            );
+
+            shadow_pass.set_pipeline(&self.gltf_shadow_render_pipeline);
+            for scene in &self.gltf_scenes {
+                shadow_pass.draw_gltf_scene_depth(scene);
+            }
+        }
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render Pass"),
+                // With MSAA the samples only need to last until they're resolved into the frame
+                color_attachments: &[Some(match &self.msaa_texture {
+                    Some(msaa_view) => wgpu::RenderPassColorAttachment {
+                        view: msaa_view,
+                        resolve_target: Some(view),
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(self.clear_color),
+                            store: wgpu::StoreOp::Discard,
+                        },
+                    },
+                    None => wgpu::RenderPassColorAttachment {
+                        view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(self.clear_color),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_texture.view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(self.depth_clear_value()),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                occlusion_query_set: None,
+                timestamp_writes: None,
+            });
+
+            if let Some(skybox) = &self.skybox {
+                skybox.draw(&mut render_pass, &self.camera_bind_group);
+            }
+
+            render_pass.set_pipeline(&self.render_pipeline);
+
+            render_pass.set_bind_group(0, &self.diffuse_bind_group, &[]);
+            render_pass.set_bind_group(1, &self.camera_bind_group, &[]);
+
+            render_pass.set_vertex_buffer(0, self.polygon_buffer.vertex_buffer.slice(..));
+            render_pass.set_vertex_buffer(1, self.instance_buffer.buffer.slice(..));
+            render_pass.set_index_buffer(
+                self.polygon_buffer.index_buffer.slice(..),
+                self.polygon_buffer.index_format,
+            );
+
+            for instances in polygon_ranges {
+                render_pass.draw_indexed(0..self.polygon_buffer.num_indices, 0, instances);
+            }
+
+            render_pass.set_pipeline(&self.lit_render_pipeline);
+            render_pass.set_bind_group(2, self.lights.bind_group(), &[]);
+            render_pass.set_bind_group(3, self.shadow_map.bind_group(), &[]);
+            render_pass.set_vertex_buffer(0, self.cube_buffer.vertex_buffer.slice(..));
+            render_pass.set_vertex_buffer(1, self.cube_instance_buffer.buffer.slice(..));
+            render_pass.set_index_buffer(
+                self.cube_buffer.index_buffer.slice(..),
+                self.cube_buffer.index_format,
+            );
+            for instances in cube_ranges {
+                render_pass.draw_indexed(0..self.cube_buffer.num_indices, 0, instances);
+            }
+
+            render_pass.set_bind_group(0, &self.floor_material.bind_group, &[]);
+            render_pass.set_vertex_buffer(1, self.floor_instance_buffer.buffer.slice(..));
+            for instances in floor_ranges {
+                render_pass.draw_indexed(0..self.cube_buffer.num_indices, 0, instances);
+            }
+
+            if !self.gltf_scenes.is_empty() {
+                // Every group but the material's is shared with the lit pipeline,
+                // but they're bound again since group 0's layout is different
+                render_pass.set_pipeline(&self.gltf_render_pipeline);
+                render_pass.set_bind_group(1, &self.camera_bind_group, &[]);
+                render_pass.set_bind_group(2, self.lights.bind_group(), &[]);
+                render_pass.set_bind_group(3, self.shadow_map.bind_group(), &[]);
+                for (scene, ranges) in self.gltf_scenes.iter().zip(&gltf_ranges) {
+                    render_pass.draw_gltf_scene(scene, ranges);
+                }
+            }
+
+            render_pass.set_pipeline(&self.light_render_pipeline);
+            render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+            render_pass.set_bind_group(1, self.lights.bind_group(), &[]);
+            render_pass.set_vertex_buffer(0, self.cube_buffer.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(
+                self.cube_buffer.index_buffer.slice(..),
+                self.cube_buffer.index_format,
+            );
+            // One gizmo per light, the shader looks the light up by instance index
+            render_pass.draw_indexed(
+                0..self.cube_buffer.num_indices,
+                0,
+                0..self.lights.lights().len() as u32,
+            );
+        }
+
+        cull_stats
+    }
+}
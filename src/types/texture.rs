@@ -1,30 +1,196 @@
-use image::GenericImageView;
 use anyhow::*;
+use image::GenericImageView;
+use wgpu::util::DeviceExt;
+
+use super::{equirect, mipmaps};
+
+// How a texture is filtered when it's drawn bigger or smaller than it is
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Filtering {
+    // Nearest texel everywhere, for pixel art. Bound with a non-filtering sampler.
+    Nearest,
+    // Linear when magnified, nearest texel and nearest mip level when minified.
+    // Cheap, but shimmers on anything far away.
+    #[default]
+    Bilinear,
+    // Linear within a mip level and between the two closest levels
+    Trilinear,
+    // Trilinear, plus extra samples along the direction a surface slopes away
+    // from the camera, so floors and walls at a shallow angle stay sharp. The
+    // value (1 - 16) is clamped to what the device supports.
+    Anisotropic(u16),
+}
+
+// Whether the texels are colors that were stored with sRGB gamma (photos, base
+// color maps) or plain numbers (normal, roughness and other data maps). Data
+// loaded as sRGB gets bent by the gamma curve when it's sampled.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ColorSpace {
+    #[default]
+    Srgb,
+    Linear,
+}
+
+impl ColorSpace {
+    pub fn format(&self) -> wgpu::TextureFormat {
+        match self {
+            ColorSpace::Srgb => wgpu::TextureFormat::Rgba8UnormSrgb,
+            ColorSpace::Linear => wgpu::TextureFormat::Rgba8Unorm,
+        }
+    }
+}
+
+// Everything about how a texture is created and sampled, the same idea as
+// wgpu::TextureDescriptor plus the sampler
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TextureOptions {
+    pub color_space: ColorSpace,
+    // What happens outside 0.0 - 1.0: ClampToEdge, Repeat or MirrorRepeat
+    pub address_mode_u: wgpu::AddressMode,
+    pub address_mode_v: wgpu::AddressMode,
+    pub filtering: Filtering,
+    // Fills in a full mip chain on the GPU after the image is uploaded
+    pub generate_mipmaps: bool,
+    // Added to TEXTURE_BINDING | COPY_DST, e.g. RENDER_ATTACHMENT to draw into the
+    // texture or STORAGE_BINDING to write it from a compute shader
+    pub usage: wgpu::TextureUsages,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl TextureOptions {
+    pub const DEFAULT: Self = Self {
+        color_space: ColorSpace::Srgb,
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        filtering: Filtering::Bilinear,
+        generate_mipmaps: false,
+        usage: wgpu::TextureUsages::empty(),
+    };
+
+    // Mipmapped and trilinear filtered, what most textures on 3D models want
+    pub const TRILINEAR: Self = Self {
+        generate_mipmaps: true,
+        filtering: Filtering::Trilinear,
+        ..Self::DEFAULT
+    };
+
+    pub fn anisotropic(max_anisotropy: u16) -> Self {
+        Self {
+            generate_mipmaps: true,
+            filtering: Filtering::Anisotropic(max_anisotropy),
+            ..Self::DEFAULT
+        }
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.color_space.format()
+    }
+
+    pub fn usage(&self) -> wgpu::TextureUsages {
+        let mut usage =
+            self.usage | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST;
+        if self.generate_mipmaps {
+            // The smaller levels are drawn into, so the texture has to be a render target too
+            usage |= wgpu::TextureUsages::RENDER_ATTACHMENT;
+        }
+        usage
+    }
+
+    pub fn sampler_descriptor(&self) -> wgpu::SamplerDescriptor<'static> {
+        use wgpu::FilterMode::{Linear, Nearest};
+        let (mag_filter, min_filter, mipmap_filter, anisotropy_clamp) = match self.filtering {
+            Filtering::Nearest => (Nearest, Nearest, Nearest, 1),
+            Filtering::Bilinear => (Linear, Nearest, Nearest, 1),
+            Filtering::Trilinear => (Linear, Linear, Linear, 1),
+            // wgpu only allows anisotropy when every filter is Linear
+            Filtering::Anisotropic(max) => (Linear, Linear, Linear, max.clamp(1, 16)),
+        };
+        wgpu::SamplerDescriptor {
+            address_mode_u: self.address_mode_u,
+            address_mode_v: self.address_mode_v,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter,
+            min_filter,
+            mipmap_filter,
+            anisotropy_clamp,
+            ..Default::default()
+        }
+    }
+
+    // The texture (binding 0) and sampler (binding 1) entries of a bind group
+    // layout that textures made with these options can be bound to. Only the
+    // filtering matters here, so textures that differ in anything else can
+    // share a layout.
+    pub fn layout_entries(
+        &self,
+        visibility: wgpu::ShaderStages,
+    ) -> [wgpu::BindGroupLayoutEntry; 2] {
+        let filterable = self.filtering != Filtering::Nearest;
+        [
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility,
+                ty: wgpu::BindingType::Texture {
+                    multisampled: false,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    sample_type: wgpu::TextureSampleType::Float { filterable },
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility,
+                // A filtering sampler can only be used with a filterable texture entry
+                ty: wgpu::BindingType::Sampler(if filterable {
+                    wgpu::SamplerBindingType::Filtering
+                } else {
+                    wgpu::SamplerBindingType::NonFiltering
+                }),
+                count: None,
+            },
+        ]
+    }
+}
 
 pub struct Texture {
     #[allow(unused)]
     pub texture: wgpu::Texture,
     pub view: wgpu::TextureView,
     pub sampler: wgpu::Sampler,
+    pub options: TextureOptions,
 }
 
 impl Texture {
     pub fn from_bytes(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-        bytes: &[u8], 
-        label: &str
+        bytes: &[u8],
+        label: &str,
+        options: TextureOptions,
     ) -> Result<Self> {
         let img = image::load_from_memory(bytes)?;
-        Self::from_image(device, queue, &img, Some(label))
+        Self::from_image(device, queue, &img, Some(label), options)
     }
 
     pub fn from_image(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         img: &image::DynamicImage,
-        label: Option<&str>
+        label: Option<&str>,
+        options: TextureOptions,
     ) -> Result<Self> {
+        // Storage textures can only be written as plain numbers
+        ensure!(
+            !(options.usage.contains(wgpu::TextureUsages::STORAGE_BINDING)
+                && options.color_space == ColorSpace::Srgb),
+            "sRGB textures can't be used as storage textures, use ColorSpace::Linear"
+        );
+
         let rgba = img.to_rgba8();
         let dimensions = img.dimensions();
 
@@ -33,18 +199,21 @@ impl Texture {
             height: dimensions.1,
             depth_or_array_layers: 1,
         };
-        let texture = device.create_texture(
-            &wgpu::TextureDescriptor {
-                label,
-                size,
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Rgba8UnormSrgb,
-                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-                view_formats: &[],
-            }
-        );
+        let mip_level_count = if options.generate_mipmaps {
+            mipmaps::mip_level_count(size.width, size.height)
+        } else {
+            1
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label,
+            size,
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: options.format(),
+            usage: options.usage(),
+            view_formats: &[],
+        });
 
         queue.write_texture(
             wgpu::TexelCopyTextureInfo {
@@ -61,20 +230,255 @@ impl Texture {
             },
             size,
         );
+        mipmaps::generate_mipmaps(device, queue, &texture);
 
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let sampler = device.create_sampler(
-            &wgpu::SamplerDescriptor {
-                address_mode_u: wgpu::AddressMode::ClampToEdge,
-                address_mode_v: wgpu::AddressMode::ClampToEdge,
-                address_mode_w: wgpu::AddressMode::ClampToEdge,
-                mag_filter: wgpu::FilterMode::Linear,
-                min_filter: wgpu::FilterMode::Nearest,
-                mipmap_filter: wgpu::FilterMode::Nearest,
-                ..Default::default()
-            }
+        let sampler = device.create_sampler(&options.sampler_descriptor());
+
+        Ok(Self {
+            texture,
+            view,
+            sampler,
+            options,
+        })
+    }
+}
+
+// Six square textures making up the inside of a cube, sampled with a direction
+// instead of texture coordinates. Used for skyboxes and reflections.
+pub struct CubeTexture {
+    #[allow(unused)]
+    pub texture: wgpu::Texture,
+    // A Cube view of all six faces
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+}
+
+impl CubeTexture {
+    // What HDR images are converted to. Half floats keep the range of the
+    // original (the sun can be far brighter than 1.0) and can still be filtered.
+    pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+    // `faces` go in the order +X, -X, +Y, -Y, +Z, -Z (right, left, top, bottom,
+    // front, back), all square and the same size.
+    pub fn from_faces(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        faces: &[image::DynamicImage; 6],
+        label: Option<&str>,
+        color_space: ColorSpace,
+    ) -> Result<Self> {
+        let (size, _) = faces[0].dimensions();
+        // wgpu can't make a texture with no texels
+        ensure!(size > 0, "cubemap faces can't be empty");
+        for (i, face) in faces.iter().enumerate() {
+            ensure!(
+                face.dimensions() == (size, size),
+                "cubemap face {i} is {:?}, but every face has to be {size}x{size}",
+                face.dimensions()
+            );
+        }
+
+        let texture = Self::create_texture(
+            device,
+            size,
+            color_space.format(),
+            wgpu::TextureUsages::empty(),
+            label,
         );
+        for (i, face) in faces.iter().enumerate() {
+            queue.write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    aspect: wgpu::TextureAspect::All,
+                    texture: &texture,
+                    mip_level: 0,
+                    // Each face is one layer of the texture
+                    origin: wgpu::Origin3d {
+                        x: 0,
+                        y: 0,
+                        z: i as u32,
+                    },
+                },
+                &face.to_rgba8(),
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * size),
+                    rows_per_image: Some(size),
+                },
+                wgpu::Extent3d {
+                    width: size,
+                    height: size,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        Ok(Self::from_texture(device, texture))
+    }
+
+    // Projects a panorama (2:1, longitude across and latitude down) onto the six
+    // faces on the GPU. The middle of the panorama ends up facing +X.
+    pub fn from_equirectangular(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        img: &image::DynamicImage,
+        face_size: u32,
+        label: Option<&str>,
+    ) -> Result<Self> {
+        ensure!(face_size > 0, "cubemap faces can't be empty");
+
+        let pixels = img.to_rgba32f();
+        let (width, height) = pixels.dimensions();
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let equirect = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("Equirectangular Texture"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba32Float,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            bytemuck::cast_slice(pixels.as_raw()),
+        );
+
+        let texture = Self::create_texture(
+            device,
+            face_size,
+            Self::HDR_FORMAT,
+            wgpu::TextureUsages::RENDER_ATTACHMENT,
+            label,
+        );
+        equirect::equirect_to_cube(
+            device,
+            queue,
+            &equirect.create_view(&wgpu::TextureViewDescriptor::default()),
+            &texture,
+        );
+
+        Ok(Self::from_texture(device, texture))
+    }
+
+    // An equirectangular .hdr (Radiance) file, or any other image format the
+    // image crate was built with.
+    pub fn from_hdr_bytes(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        bytes: &[u8],
+        face_size: u32,
+        label: &str,
+    ) -> Result<Self> {
+        let img = image::load_from_memory(bytes)?;
+        Self::from_equirectangular(device, queue, &img, face_size, Some(label))
+    }
+
+    fn create_texture(
+        device: &wgpu::Device,
+        size: u32,
+        format: wgpu::TextureFormat,
+        usage: wgpu::TextureUsages,
+        label: Option<&str>,
+    ) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label,
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 6,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: usage | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        })
+    }
+
+    fn from_texture(device: &wgpu::Device, texture: wgpu::Texture) -> Self {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Cube View"),
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+        // Linear everywhere so the seams between faces blend together
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Cube Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            texture,
+            view,
+            sampler,
+        }
+    }
+}
+
+pub struct DepthTexture {
+    #[allow(unused)]
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+}
+
+impl DepthTexture {
+    pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+    // The depth texture has to be the same size as the color target it's used
+    // with, so this needs to be called again whenever the surface is resized. It
+    // also needs the same sample count.
+    pub fn create_depth_texture(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        sample_count: u32,
+    ) -> Self {
+        let size = wgpu::Extent3d {
+            width: config.width.max(1),
+            height: config.height.max(1),
+            depth_or_array_layers: 1,
+        };
+        // TEXTURE_BINDING lets the depth be sampled later on, e.g. to debug it. A
+        // multisampled one couldn't be sampled like that anyway, and on GL having
+        // it stops the color samples from being resolved.
+        let usage = if sample_count == 1 {
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING
+        } else {
+            wgpu::TextureUsages::RENDER_ATTACHMENT
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Depth Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::DEPTH_FORMAT,
+            usage,
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        Self { texture, view }
+    }
 
-        Ok(Self { texture, view, sampler })
+    pub fn depth_stencil_state(depth_compare: wgpu::CompareFunction) -> wgpu::DepthStencilState {
+        wgpu::DepthStencilState {
+            format: Self::DEPTH_FORMAT,
+            depth_write_enabled: true,
+            depth_compare, // Less draws a fragment if it's in front of what's already there
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }
     }
 }
@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+use web_time::Instant;
+
+// Measures how long each frame took, so things can move at the same speed no
+// matter the frame rate.
+pub struct FrameTimer {
+    last_tick: Option<Instant>,
+    delta: Duration,
+    elapsed: Duration,
+    // A frame that took longer than this (the window was being dragged, the tab was
+    // in the background...) only counts as this long, so nothing jumps too far.
+    pub max_delta: Duration,
+}
+
+impl Default for FrameTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameTimer {
+    pub fn new() -> Self {
+        Self {
+            last_tick: None,
+            delta: Duration::ZERO,
+            elapsed: Duration::ZERO,
+            max_delta: Duration::from_millis(250),
+        }
+    }
+
+    // Call once per frame. Returns the time since the previous call, which is
+    // zero the first time.
+    pub fn tick(&mut self) -> Duration {
+        self.tick_at(Instant::now())
+    }
+
+    pub fn tick_at(&mut self, now: Instant) -> Duration {
+        self.delta = match self.last_tick {
+            Some(last) => now.saturating_duration_since(last).min(self.max_delta),
+            None => Duration::ZERO,
+        };
+        self.last_tick = Some(now);
+        self.elapsed += self.delta;
+        self.delta
+    }
+
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    // Total of all the deltas so far
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+// Runs updates at a fixed rate regardless of the frame rate, for things (like
+// physics) that behave differently with different step sizes. Frame time builds
+// up in an accumulator and is paid out a whole step at a time.
+pub struct FixedTimestep {
+    step: Duration,
+    accumulator: Duration,
+    // Stops a slow frame from needing more steps, which make the next frame even
+    // slower, and so on. Time past this many steps is dropped.
+    pub max_steps: u32,
+}
+
+impl FixedTimestep {
+    pub fn new(step: Duration) -> Self {
+        assert!(!step.is_zero(), "a fixed timestep can't be zero");
+        Self {
+            step,
+            accumulator: Duration::ZERO,
+            max_steps: 8,
+        }
+    }
+
+    // `hz` steps a second, which has to be a finite number above zero
+    pub fn from_hz(hz: f64) -> Self {
+        assert!(
+            hz > 0.0 && hz.is_finite(),
+            "a fixed timestep needs a rate above 0 Hz, not {hz}"
+        );
+        Self::new(Duration::from_secs_f64(1.0 / hz))
+    }
+
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    // Adds a frame's worth of time and returns how many fixed steps to run for it.
+    pub fn advance(&mut self, delta: Duration) -> u32 {
+        self.accumulator += delta;
+
+        let mut steps = 0;
+        while self.accumulator >= self.step {
+            if steps == self.max_steps {
+                self.accumulator = Duration::ZERO;
+                break;
+            }
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+        steps
+    }
+
+    // How far (0.0 - 1.0) the current frame is between the last fixed step and the
+    // next one. Blend the previous and current state by this to render smoothly.
+    pub fn alpha(&self) -> f32 {
+        self.accumulator.as_secs_f32() / self.step.as_secs_f32()
+    }
+}
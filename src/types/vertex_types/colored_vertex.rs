@@ -1,36 +1,24 @@
-use super::Vertex;
+use super::{MeshVertex, Vertex};
 
 #[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
 pub struct ColoredVertex {
     position: [f32; 3],
     color: [f32; 3],
 }
 
-impl Vertex for ColoredVertex {
-    fn desc() -> wgpu::VertexBufferLayout<'static> {
-        wgpu::VertexBufferLayout {
-            array_stride: std::mem::size_of::<ColoredVertex>() as wgpu::BufferAddress, // defines how wide a vertex will be in memory
-            step_mode: wgpu::VertexStepMode::Vertex, // tells the pipeline whether to handle the vertices in a per-vertex or per-instance case
-            attributes: &[ // describe the individual parts of the vertex
-                wgpu::VertexAttribute {
-                    offset: 0, // offset in bytes until an attribute starts
-                    shader_location: 0, // tells the shader where to store this attribute at
-                    format: wgpu::VertexFormat::Float32x3, // tells the shader what the shape of the attribute is
-                },
-                wgpu::VertexAttribute {
-                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
-                    shader_location: 1,
-                    format: wgpu::VertexFormat::Float32x3,
-                }
-            ]
-        }
+impl MeshVertex for ColoredVertex {
+    fn position(&self) -> [f32; 3] {
+        self.position
     }
 }
 
-// lib.rs
 impl ColoredVertex {
-    pub fn _generate_polygon(num_sides: u16, radius: f32) -> (Vec<ColoredVertex>, Vec<u16>) {
+    pub const fn new(position: [f32; 3], color: [f32; 3]) -> Self {
+        Self { position, color }
+    }
+
+    pub fn generate_polygon(num_sides: u16, radius: f32) -> (Vec<ColoredVertex>, Vec<u16>) {
         let angle = std::f32::consts::PI * 2.0 / num_sides as f32;
         let vertices = (0..(num_sides * 3))
             .map(|i| {
@@ -43,11 +31,10 @@ impl ColoredVertex {
             })
             .collect();
 
-            let num_triangles = (num_sides * 3) - 2;
-            let indices = (1u16..num_triangles + 1)
-                .into_iter()
-                .flat_map(|i| vec![0, i + 1, i])
-                .collect();
+        let num_triangles = (num_sides * 3) - 2;
+        let indices = (1u16..num_triangles + 1)
+            .flat_map(|i| vec![0, i + 1, i])
+            .collect();
 
         (vertices, indices)
     }
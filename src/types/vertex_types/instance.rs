@@ -0,0 +1,63 @@
+use cgmath::{One, Zero};
+
+use super::Vertex;
+
+// Where one copy of a mesh sits in the world. Many of these can be drawn
+// with a single draw call by uploading them as an instance buffer.
+#[derive(Copy, Clone, Debug)]
+pub struct Instance {
+    pub position: cgmath::Vector3<f32>,
+    pub rotation: cgmath::Quaternion<f32>,
+    pub scale: cgmath::Vector3<f32>,
+}
+
+impl Default for Instance {
+    fn default() -> Self {
+        Self {
+            position: cgmath::Vector3::zero(),
+            rotation: cgmath::Quaternion::one(),
+            scale: cgmath::Vector3::new(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+impl Instance {
+    pub fn to_raw(&self) -> InstanceRaw {
+        let model = cgmath::Matrix4::from_translation(self.position)
+            * cgmath::Matrix4::from(self.rotation)
+            * cgmath::Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z);
+
+        InstanceRaw {
+            model: model.into(),
+        }
+    }
+}
+
+// What actually goes into the instance buffer: the model matrix, since
+// quaternions can't be used directly in wgsl. The shader only moves on to the
+// next InstanceRaw once it's done with every vertex of the current instance.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+#[instance]
+pub struct InstanceRaw {
+    // A mat4 takes up four vertex slots, one vec4 per column. Locations start at 5
+    // to leave room for the attributes of the mesh's own vertex type.
+    #[location(5)]
+    model: [[f32; 4]; 4],
+}
+
+impl InstanceRaw {
+    pub fn model(&self) -> cgmath::Matrix4<f32> {
+        self.model.into()
+    }
+}
+
+// For transforms that can't be split back into position/rotation/scale, such
+// as the world transforms of nodes in a scene hierarchy.
+impl From<cgmath::Matrix4<f32>> for InstanceRaw {
+    fn from(model: cgmath::Matrix4<f32>) -> Self {
+        Self {
+            model: model.into(),
+        }
+    }
+}
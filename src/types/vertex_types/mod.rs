@@ -1,6 +1,35 @@
 pub mod colored_vertex;
+pub mod instance;
+pub mod model_vertex;
+pub mod pbr_vertex;
 pub mod textured_vertex;
 
+// Usually derived, which works out the layout from the fields
+pub use wgpu_ex_derive::Vertex;
+
 pub trait Vertex {
     fn desc() -> wgpu::VertexBufferLayout<'static>;
-}
\ No newline at end of file
+}
+
+// Vertices that make up a mesh, as opposed to per-instance data. Used to work
+// out the mesh's bounds.
+pub trait MeshVertex: Vertex {
+    fn position(&self) -> [f32; 3];
+
+    // Only the position, at location 0, for passes that don't need anything else
+    // (like the shadow pass). Every vertex type has it as its first field.
+    fn position_desc() -> wgpu::VertexBufferLayout<'static>
+    where
+        Self: Sized,
+    {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Self>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[wgpu::VertexAttribute {
+                offset: 0,
+                shader_location: 0,
+                format: wgpu::VertexFormat::Float32x3,
+            }],
+        }
+    }
+}
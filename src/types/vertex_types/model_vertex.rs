@@ -0,0 +1,51 @@
+use super::{MeshVertex, Vertex};
+
+// Vertices of meshes loaded from model files, which carry normals on top of
+// what TexturedVertex has.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+pub struct ModelVertex {
+    pub position: [f32; 3],
+    pub tex_coords: [f32; 2],
+    pub normal: [f32; 3],
+}
+
+impl MeshVertex for ModelVertex {
+    fn position(&self) -> [f32; 3] {
+        self.position
+    }
+}
+
+impl ModelVertex {
+    // An axis aligned cube centered on the origin, with its own normals and
+    // texture coordinates for every face.
+    pub fn cube(half_extent: f32) -> (Vec<ModelVertex>, Vec<u16>) {
+        // (normal, the two axes spanning the face)
+        let faces: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+            ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+            ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+            ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+            ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+            ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+            ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+        ];
+
+        let mut vertices = Vec::with_capacity(24);
+        let mut indices = Vec::with_capacity(36);
+        for (normal, u, v) in faces {
+            let base = vertices.len() as u16;
+            for (su, sv) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+                let position = [0, 1, 2].map(|i| (normal[i] + u[i] * su + v[i] * sv) * half_extent);
+                vertices.push(ModelVertex {
+                    position,
+                    tex_coords: [(su + 1.0) / 2.0, (1.0 - sv) / 2.0],
+                    normal,
+                });
+            }
+            // Counter clockwise when looking at the face from outside
+            indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        }
+
+        (vertices, indices)
+    }
+}
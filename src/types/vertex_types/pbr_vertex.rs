@@ -0,0 +1,18 @@
+use super::{MeshVertex, Vertex};
+
+// Vertices for physically based materials. The tangent's w holds the
+// handedness of the bitangent, as in glTF.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+pub struct PbrVertex {
+    pub position: [f32; 3],
+    pub tex_coords: [f32; 2],
+    pub normal: [f32; 3],
+    pub tangent: [f32; 4],
+}
+
+impl MeshVertex for PbrVertex {
+    fn position(&self) -> [f32; 3] {
+        self.position
+    }
+}
@@ -0,0 +1,103 @@
+use wgpu_ex::types::{cli::Args, examples};
+
+fn parse(args: &[&str]) -> anyhow::Result<Option<Args>> {
+    Args::parse(args.iter().map(|arg| arg.to_string()))
+}
+
+#[test]
+fn no_arguments_gives_the_defaults() {
+    let args = parse(&[]).unwrap().unwrap();
+    assert_eq!(args.example, None);
+    assert_eq!(args.size, None);
+    assert!(!args.fullscreen);
+    assert_eq!(args.frames, None);
+    assert_eq!(args.render.backends, wgpu::Backends::PRIMARY);
+    assert_eq!(args.render.present_mode, None);
+    assert_eq!(args.render.sample_count, 1);
+}
+
+#[test]
+fn every_option_is_parsed() {
+    let args = parse(&[
+        "--example",
+        "textures",
+        "--size=1280x720",
+        "--fullscreen",
+        "--backend",
+        "gl",
+        "--power",
+        "high",
+        "--present-mode",
+        "mailbox",
+        "--msaa",
+        "4",
+        "--frames=10",
+        "--hot-reload",
+    ])
+    .unwrap()
+    .unwrap();
+    assert_eq!(args.example, examples::find("textures"));
+    assert_eq!(args.size, Some(winit::dpi::PhysicalSize::new(1280, 720)));
+    assert!(args.fullscreen);
+    assert_eq!(args.render.backends, wgpu::Backends::GL);
+    assert_eq!(
+        args.render.power_preference,
+        wgpu::PowerPreference::HighPerformance
+    );
+    assert_eq!(args.render.present_mode, Some(wgpu::PresentMode::Mailbox));
+    assert_eq!(args.render.sample_count, 4);
+    assert_eq!(args.frames, Some(10));
+    assert!(args.hot_reload);
+
+    // Examples can be picked by number too
+    assert_eq!(
+        parse(&["--example", "1"]).unwrap().unwrap().example,
+        Some(0)
+    );
+}
+
+#[test]
+fn help_stops_parsing() {
+    assert!(
+        parse(&["--backend", "gl", "--help", "--nonsense"])
+            .unwrap()
+            .is_none()
+    );
+    assert!(parse(&["-h"]).unwrap().is_none());
+}
+
+#[test]
+fn bad_arguments_say_what_is_wrong() {
+    let error = |args: &[&str]| {
+        format!(
+            "{:#}",
+            parse(args).err().expect("the arguments should be rejected")
+        )
+    };
+    assert_eq!(error(&["--nonsense"]), "unknown argument --nonsense");
+    assert_eq!(error(&["--frames"]), "--frames needs a value");
+    assert_eq!(
+        error(&["--frames", "0"]),
+        "--frames needs a number of frames above 0, not 0"
+    );
+    assert_eq!(
+        error(&["--msaa", "3"]),
+        "--msaa takes 1, 2, 4, 8 or 16 samples, not 3"
+    );
+    assert_eq!(
+        error(&["--size", "1280"]),
+        "--size needs a width and height like 1280x720, not 1280"
+    );
+    assert_eq!(
+        error(&["--backend", "dx11"]),
+        "unknown backend dx11, try vulkan, gl or any"
+    );
+    assert_eq!(
+        error(&["--fullscreen=yes"]),
+        "--fullscreen doesn't take a value"
+    );
+    assert!(
+        error(&["--example", "challenge_3"])
+            .starts_with("there's no example called challenge_3, try one of clear_color, ")
+    );
+}
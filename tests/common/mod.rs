@@ -0,0 +1,191 @@
+// Each test binary uses its own share of these
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+use image::{Rgba, RgbaImage};
+use wgpu_ex::types::state::{RenderOptions, State};
+
+// Reference images are checked in next to the tests. Run with
+// UPDATE_GOLDEN=1 to (re)generate them from the current output.
+const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+
+pub struct Tolerance {
+    // Largest difference allowed in any one channel before a pixel counts as wrong.
+    pub per_channel: u8,
+    // Fraction of pixels (0.0 - 1.0) that are allowed to be wrong.
+    pub max_failing_fraction: f64,
+    // Largest mean error per channel, in 0-255 units. Catches a small shift over the
+    // whole image that stays inside per_channel everywhere.
+    pub max_mean_error: f64,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        // Software rasterizers and GPUs round slightly differently, mostly at triangle edges.
+        Self {
+            per_channel: 2,
+            max_failing_fraction: 0.005,
+            max_mean_error: 1.0,
+        }
+    }
+}
+
+pub struct Comparison {
+    pub failing_pixels: u64,
+    pub total_pixels: u64,
+    // Mean absolute error per channel, in 0-255 units.
+    pub mean_error: f64,
+    pub diff: RgbaImage,
+}
+
+impl Comparison {
+    pub fn failing_fraction(&self) -> f64 {
+        self.failing_pixels as f64 / self.total_pixels as f64
+    }
+
+    pub fn passes(&self, tolerance: &Tolerance) -> bool {
+        self.failing_fraction() <= tolerance.max_failing_fraction
+            && self.mean_error <= tolerance.max_mean_error
+    }
+}
+
+pub fn compare(actual: &RgbaImage, expected: &RgbaImage, tolerance: &Tolerance) -> Comparison {
+    assert_eq!(
+        actual.dimensions(),
+        expected.dimensions(),
+        "image dimensions differ"
+    );
+
+    let mut diff = RgbaImage::new(actual.width(), actual.height());
+    let mut failing_pixels = 0;
+    let mut error_sum = 0u64;
+
+    for (x, y, a) in actual.enumerate_pixels() {
+        let e = expected.get_pixel(x, y);
+        let channel_diffs: Vec<u8> =
+            a.0.iter()
+                .zip(e.0.iter())
+                .map(|(a, e)| a.abs_diff(*e))
+                .collect();
+        let worst = channel_diffs.iter().copied().max().unwrap_or(0);
+        error_sum += channel_diffs.iter().map(|d| *d as u64).sum::<u64>();
+
+        // Wrong pixels show up red, everything else is a faded copy of the expected image.
+        if worst > tolerance.per_channel {
+            failing_pixels += 1;
+            diff.put_pixel(x, y, Rgba([255, 0, 0, 255]));
+        } else {
+            let [r, g, b, _] = e.0;
+            diff.put_pixel(x, y, Rgba([r / 4, g / 4, b / 4, 255]));
+        }
+    }
+
+    let total_pixels = actual.width() as u64 * actual.height() as u64;
+    Comparison {
+        failing_pixels,
+        total_pixels,
+        mean_error: error_sum as f64 / (total_pixels * 4) as f64,
+        diff,
+    }
+}
+
+// A headless State. A machine with no adapter to render with at all fails the
+// test, unless WGPU_EX_ALLOW_NO_GPU is set to skip it instead (and None is returned).
+pub fn headless_state(width: u32, height: u32) -> Option<State<'static>> {
+    headless_state_with(width, height, &RenderOptions::default())
+}
+
+pub fn headless_state_with(
+    width: u32,
+    height: u32,
+    options: &RenderOptions,
+) -> Option<State<'static>> {
+    match pollster::block_on(State::new_headless_with(
+        width,
+        height,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+        options,
+    )) {
+        Ok(state) => Some(state),
+        Err(e) => no_gpu(&format!("could not create a headless State: {e:#}")),
+    }
+}
+
+// A device of our own with no optional features, for tests that don't need a
+// whole State. No adapter fails the test, the same as headless_state.
+pub fn device() -> Option<(wgpu::Device, wgpu::Queue)> {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::from_env().unwrap_or(wgpu::Backends::all()),
+        ..Default::default()
+    });
+    let Some(adapter) =
+        pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+    else {
+        return no_gpu("no adapter found");
+    };
+    match pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None)) {
+        Ok(device) => Some(device),
+        Err(e) => no_gpu(&format!("could not create a device: {e}")),
+    }
+}
+
+fn no_gpu<T>(why: &str) -> Option<T> {
+    if std::env::var_os("WGPU_EX_ALLOW_NO_GPU").is_none() {
+        panic!("{why}. Set WGPU_EX_ALLOW_NO_GPU=1 to skip the tests that need a GPU instead");
+    }
+    eprintln!("SKIPPED, {why}");
+    None
+}
+
+pub fn render_headless(
+    width: u32,
+    height: u32,
+    setup: impl FnOnce(&mut State),
+) -> Option<RgbaImage> {
+    let mut state = headless_state(width, height)?;
+    setup(&mut state);
+    state.update(std::time::Duration::ZERO);
+    Some(state.capture().expect("failed to capture frame"))
+}
+
+pub fn assert_matches_golden(name: &str, actual: &RgbaImage, tolerance: &Tolerance) {
+    let golden_path = PathBuf::from(GOLDEN_DIR).join(format!("{name}.png"));
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(GOLDEN_DIR).unwrap();
+        actual.save(&golden_path).unwrap();
+        return;
+    }
+
+    let expected = image::open(&golden_path)
+        .unwrap_or_else(|e| {
+            panic!(
+                "missing reference image {} ({e}), run with UPDATE_GOLDEN=1 to create it",
+                golden_path.display()
+            )
+        })
+        .to_rgba8();
+
+    let comparison = compare(actual, &expected, tolerance);
+    if !comparison.passes(tolerance) {
+        let out_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden");
+        std::fs::create_dir_all(&out_dir).unwrap();
+        let actual_path = out_dir.join(format!("{name}.actual.png"));
+        let diff_path = out_dir.join(format!("{name}.diff.png"));
+        actual.save(&actual_path).unwrap();
+        comparison.diff.save(&diff_path).unwrap();
+
+        panic!(
+            "{name}: {} of {} pixels differ by more than {} (at most {:.1}% may), mean error {:.3} (at most {:.3}), output written to {} and {}",
+            comparison.failing_pixels,
+            comparison.total_pixels,
+            tolerance.per_channel,
+            tolerance.max_failing_fraction * 100.0,
+            comparison.mean_error,
+            tolerance.max_mean_error,
+            actual_path.display(),
+            diff_path.display(),
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use cgmath::Deg;
+use wgpu_ex::types::{
+    camera_types::camera::{Camera, Projection},
+    culling::{Aabb, BoundingSphere, CullStats, Frustum},
+    vertex_types::instance::Instance,
+};
+
+fn frustum() -> Frustum {
+    // Looking down -z from the origin
+    let camera = Camera {
+        eye: (0.0, 0.0, 0.0).into(),
+        target: (0.0, 0.0, -1.0).into(),
+        up: cgmath::Vector3::unit_y(),
+        aspect: 1.0,
+        projection: Projection::Perspective {
+            fovy: Deg(90.0),
+            znear: 0.1,
+            zfar: 100.0,
+        },
+        reverse_z: false,
+    };
+    Frustum::from_matrix(&camera.build_view_projection_matrix())
+}
+
+fn sphere(x: f32, y: f32, z: f32, radius: f32) -> BoundingSphere {
+    BoundingSphere {
+        center: (x, y, z).into(),
+        radius,
+    }
+}
+
+#[test]
+fn bounds_from_points() {
+    let points = [[1.0, 2.0, 3.0], [-1.0, 0.0, 5.0], [0.0, 4.0, 4.0]];
+
+    let aabb = Aabb::from_points(points);
+    assert_eq!(aabb.min, (-1.0, 0.0, 3.0).into());
+    assert_eq!(aabb.max, (1.0, 4.0, 5.0).into());
+
+    let sphere = BoundingSphere::from_points(&points);
+    assert_eq!(sphere.center, (0.0, 2.0, 4.0).into());
+    // Reaching exactly as far as the furthest point, (-1, 0, 5)
+    assert!((sphere.radius - 6.0f32.sqrt()).abs() < 1e-5);
+}
+
+#[test]
+fn spheres_against_each_plane() {
+    let frustum = frustum();
+
+    assert!(frustum.intersects_sphere(&sphere(0.0, 0.0, -5.0, 1.0)));
+    // Behind the camera, past the far plane, and off to each side
+    assert!(!frustum.intersects_sphere(&sphere(0.0, 0.0, 5.0, 1.0)));
+    assert!(!frustum.intersects_sphere(&sphere(0.0, 0.0, -200.0, 1.0)));
+    assert!(!frustum.intersects_sphere(&sphere(-20.0, 0.0, -5.0, 1.0)));
+    assert!(!frustum.intersects_sphere(&sphere(20.0, 0.0, -5.0, 1.0)));
+    assert!(!frustum.intersects_sphere(&sphere(0.0, -20.0, -5.0, 1.0)));
+    assert!(!frustum.intersects_sphere(&sphere(0.0, 20.0, -5.0, 1.0)));
+    // Poking in from the side
+    assert!(frustum.intersects_sphere(&sphere(6.0, 0.0, -5.0, 1.5)));
+}
+
+#[test]
+fn boxes_and_transforms() {
+    let frustum = frustum();
+    let aabb = Aabb {
+        min: (-1.0, -1.0, -1.0).into(),
+        max: (1.0, 1.0, 1.0).into(),
+    };
+    let sphere = BoundingSphere {
+        center: (0.0, 0.0, 0.0).into(),
+        radius: 3.0f32.sqrt(),
+    };
+
+    assert!(frustum.intersects(
+        &aabb,
+        &sphere,
+        &cgmath::Matrix4::from_translation((0.0, 0.0, -5.0).into())
+    ));
+    assert!(!frustum.intersects(
+        &aabb,
+        &sphere,
+        &cgmath::Matrix4::from_translation((0.0, 0.0, 10.0).into())
+    ));
+
+    // Scaling makes the bounds bigger, far enough to reach back into view
+    let scaled = cgmath::Matrix4::from_translation((12.0, 0.0, -5.0).into())
+        * cgmath::Matrix4::from_nonuniform_scale(8.0, 1.0, 1.0);
+    assert!(frustum.intersects(&aabb, &sphere, &scaled));
+}
+
+#[test]
+fn visible_instances_are_merged_into_ranges() {
+    let frustum = frustum();
+    let aabb = Aabb {
+        min: (-0.5, -0.5, -0.5).into(),
+        max: (0.5, 0.5, 0.5).into(),
+    };
+    let sphere = BoundingSphere {
+        center: (0.0, 0.0, 0.0).into(),
+        radius: 0.9,
+    };
+
+    // In front, in front, behind, in front, behind, behind
+    let instances = [-5.0, -6.0, 5.0, -7.0, 6.0, 7.0].map(|z| {
+        Instance {
+            position: (0.0, 0.0, z).into(),
+            ..Default::default()
+        }
+        .to_raw()
+    });
+
+    let mut stats = CullStats::default();
+    let ranges = frustum.visible_instances(&aabb, &sphere, &instances, &mut stats);
+
+    assert_eq!(ranges, vec![0..2, 3..4]);
+    assert_eq!(
+        stats,
+        CullStats {
+            drawn: 3,
+            culled: 3
+        }
+    );
+}
@@ -0,0 +1,31 @@
+use wgpu_ex::types::examples::{EXAMPLES, find};
+
+#[test]
+fn examples_are_found_by_name_or_number() {
+    assert_eq!(find("clear_color"), Some(0));
+    assert_eq!(find("scene"), Some(EXAMPLES.len() - 1));
+    // Numbered from 1, like the hotkeys
+    assert_eq!(find("1"), Some(0));
+    assert_eq!(find(&EXAMPLES.len().to_string()), Some(EXAMPLES.len() - 1));
+    assert_eq!(find("0"), None);
+    assert_eq!(find(&(EXAMPLES.len() + 1).to_string()), None);
+    assert_eq!(find("challenge_3"), None);
+}
+
+#[test]
+fn there_is_exactly_one_scene_and_names_are_unique() {
+    assert_eq!(
+        EXAMPLES.iter().filter(|example| example.is_scene()).count(),
+        1
+    );
+    for (i, example) in EXAMPLES.iter().enumerate() {
+        assert_eq!(
+            find(example.name),
+            Some(i),
+            "{} is used twice",
+            example.name
+        );
+    }
+    // Only the number keys 1 to 9 pick examples
+    assert!(EXAMPLES.len() <= 9);
+}
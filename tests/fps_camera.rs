@@ -0,0 +1,83 @@
+use cgmath::{Deg, InnerSpace, MetricSpace};
+use wgpu_ex::types::camera_types::fps_camera::{FpsCamera, FpsController};
+use winit::{event::ElementState, keyboard::KeyCode};
+
+const SECOND: std::time::Duration = std::time::Duration::from_secs(1);
+
+fn assert_close(a: cgmath::Vector3<f32>, b: cgmath::Vector3<f32>) {
+    assert!((a - b).magnitude() < 1e-4, "{a:?} != {b:?}");
+}
+
+#[test]
+fn looking_at_faces_the_target() {
+    let camera = FpsCamera::looking_at((1.0, 2.0, 3.0).into(), (1.0, 0.0, 1.0).into());
+    assert_close(
+        camera.forward(),
+        cgmath::Vector3::new(0.0, -1.0, -1.0).normalize(),
+    );
+}
+
+#[test]
+fn moves_relative_to_view_direction() {
+    // Looking along -z
+    let mut camera = FpsCamera::new((0.0, 0.0, 0.0).into(), Deg(-90.0), Deg(0.0));
+    let mut controller = FpsController::new(1.0, 0.01);
+
+    controller.process_keyboard(KeyCode::KeyW, ElementState::Pressed);
+    controller.update_camera(&mut camera, SECOND);
+    assert_close(
+        camera.position - cgmath::Point3::new(0.0, 0.0, 0.0),
+        cgmath::Vector3::new(0.0, 0.0, -1.0),
+    );
+
+    controller.process_keyboard(KeyCode::KeyW, ElementState::Released);
+    controller.process_keyboard(KeyCode::KeyD, ElementState::Pressed);
+    controller.update_camera(&mut camera, SECOND);
+    assert_close(
+        camera.position - cgmath::Point3::new(0.0, 0.0, 0.0),
+        cgmath::Vector3::new(1.0, 0.0, -1.0),
+    );
+}
+
+#[test]
+fn space_and_shift_move_vertically() {
+    // Looking down at an angle, up is still straight up
+    let mut camera = FpsCamera::new((0.0, 0.0, 0.0).into(), Deg(0.0), Deg(-45.0));
+    let mut controller = FpsController::new(1.0, 0.01);
+
+    controller.process_keyboard(KeyCode::Space, ElementState::Pressed);
+    controller.update_camera(&mut camera, SECOND);
+    assert_close(
+        camera.position - cgmath::Point3::new(0.0, 0.0, 0.0),
+        cgmath::Vector3::unit_y(),
+    );
+
+    controller.process_keyboard(KeyCode::Space, ElementState::Released);
+    controller.process_keyboard(KeyCode::ShiftLeft, ElementState::Pressed);
+    controller.update_camera(&mut camera, SECOND);
+    controller.update_camera(&mut camera, SECOND);
+    assert_close(
+        camera.position - cgmath::Point3::new(0.0, 0.0, 0.0),
+        -cgmath::Vector3::unit_y(),
+    );
+}
+
+#[test]
+fn mouse_turns_and_pitch_is_clamped() {
+    let mut camera = FpsCamera::new((0.0, 0.0, 0.0).into(), Deg(0.0), Deg(0.0));
+    let mut controller = FpsController::new(1.0, 0.01);
+
+    // Mouse up looks up, far enough that it has to stop short of straight up
+    controller.process_mouse(50.0, -10_000.0);
+    controller.update_camera(&mut camera, SECOND);
+
+    assert!((camera.yaw.0 - 0.5).abs() < 1e-6);
+    assert!(camera.pitch.0 < std::f32::consts::FRAC_PI_2);
+    assert!(camera.forward().y > 0.99);
+
+    // Motion is only applied once
+    let before = camera.position;
+    controller.update_camera(&mut camera, SECOND);
+    assert!((camera.yaw.0 - 0.5).abs() < 1e-6);
+    assert_eq!(camera.position.distance(before), 0.0);
+}
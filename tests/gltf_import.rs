@@ -0,0 +1,118 @@
+use std::path::Path;
+
+use cgmath::{Matrix4, Vector3};
+use wgpu_ex::types::gltf_scene::SceneData;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+const TRIANGLE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/triangle.gltf");
+// The same file as a .glb, with the image in its binary chunk
+const TRIANGLE_GLB: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/triangle.glb");
+// And as a .gltf with its buffer in triangle.bin and its image in orange.png
+const TRIANGLE_EXTERNAL: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/tests/fixtures/triangle_external.gltf"
+);
+
+// What every version of the triangle fixture should come out as
+fn assert_is_triangle(scene: &SceneData) {
+    let primitive = &scene.meshes[0].primitives[0];
+    assert_eq!(primitive.indices, vec![0, 1, 2]);
+    assert_eq!(primitive.vertices[1].position, [1.0, 0.0, 0.0]);
+    assert_eq!(scene.materials[0].base_color_texture, Some(0));
+    assert_eq!(scene.images[0].dimensions(), (2, 2));
+    assert_eq!(scene.images[0].get_pixel(1, 1).0, [255, 128, 0, 255]);
+    assert_eq!(scene.mesh_instances().len(), 2);
+}
+
+#[test]
+fn meshes_are_read_with_generated_normals_and_tangents() {
+    let scene = SceneData::load(TRIANGLE).unwrap();
+
+    assert_eq!(scene.meshes.len(), 1);
+    assert_eq!(scene.meshes[0].name.as_deref(), Some("triangle"));
+
+    let primitive = &scene.meshes[0].primitives[0];
+    assert_eq!(primitive.indices, vec![0, 1, 2]);
+    assert_eq!(primitive.material, Some(0));
+    assert_eq!(primitive.vertices[1].position, [1.0, 0.0, 0.0]);
+    assert_eq!(primitive.vertices[2].tex_coords, [0.0, 1.0]);
+    for vertex in &primitive.vertices {
+        assert_eq!(vertex.normal, [0.0, 0.0, 1.0]);
+        assert_eq!(vertex.tangent, [1.0, 0.0, 0.0, 1.0]);
+    }
+}
+
+#[test]
+fn materials_and_embedded_images_are_read() {
+    let scene = SceneData::load(TRIANGLE).unwrap();
+
+    let material = &scene.materials[0];
+    assert_eq!(material.name.as_deref(), Some("orange"));
+    assert_eq!(material.base_color_factor, [1.0, 0.5, 0.0, 1.0]);
+    assert_eq!(material.metallic_factor, 0.25);
+    assert_eq!(material.roughness_factor, 0.75);
+    assert_eq!(material.base_color_texture, Some(0));
+    assert_eq!(material.normal_texture, None);
+
+    assert_eq!(scene.images[0].dimensions(), (2, 2));
+    assert_eq!(scene.images[0].get_pixel(1, 1).0, [255, 128, 0, 255]);
+}
+
+#[test]
+fn node_hierarchy_gives_world_transforms() {
+    let scene = SceneData::load(TRIANGLE).unwrap();
+
+    assert_eq!(scene.roots, vec![0, 2]);
+    assert_eq!(scene.nodes[0].children, vec![1]);
+
+    let mut instances = scene.mesh_instances();
+    instances.sort_by(|a, b| a.1.w.x.total_cmp(&b.1.w.x));
+
+    // The child's translation is applied on top of its parent's
+    assert_eq!(instances[0], (0, Matrix4::from_scale(2.0)));
+    assert_eq!(
+        instances[1],
+        (0, Matrix4::from_translation(Vector3::new(1.0, 2.0, 0.0)))
+    );
+}
+
+#[test]
+fn binary_glb_files_are_read() {
+    assert_is_triangle(&SceneData::load(TRIANGLE_GLB).unwrap());
+    assert_is_triangle(
+        &SceneData::from_slice(&std::fs::read(TRIANGLE_GLB).unwrap(), Path::new(".")).unwrap(),
+    );
+}
+
+#[test]
+fn external_buffers_and_images_are_read_from_next_to_the_file() {
+    assert_is_triangle(&SceneData::load(TRIANGLE_EXTERNAL).unwrap());
+
+    let bytes = std::fs::read(TRIANGLE_EXTERNAL).unwrap();
+    assert_is_triangle(&SceneData::from_slice(&bytes, Path::new(FIXTURES)).unwrap());
+    // Looked for somewhere they aren't
+    let error = SceneData::from_slice(&bytes, Path::new(env!("CARGO_MANIFEST_DIR")))
+        .err()
+        .unwrap();
+    assert!(
+        format!("{error:#}").contains("couldn't load glTF buffers"),
+        "{error:#}"
+    );
+}
+
+#[test]
+fn invalid_files_are_errors() {
+    assert!(SceneData::from_slice(b"{ not json", Path::new(".")).is_err());
+
+    // Only triangle lists can be turned into PolygonBuffers
+    let points = std::fs::read_to_string(TRIANGLE)
+        .unwrap()
+        .replace(r#""indices": 2,"#, r#""indices": 2, "mode": 0,"#);
+    let error = SceneData::from_slice(points.as_bytes(), Path::new("."))
+        .err()
+        .unwrap();
+    assert!(
+        format!("{error:#}").contains("only triangle lists are supported"),
+        "{error:#}"
+    );
+}
@@ -0,0 +1,716 @@
+mod common;
+
+use cgmath::Rotation3;
+use common::{
+    Tolerance, assert_matches_golden, compare, headless_state, headless_state_with, render_headless,
+};
+use image::{Rgba, RgbaImage};
+use wgpu_ex::types::{
+    camera_types::camera::{OrthographicSize, Projection},
+    culling::CullStats,
+    examples::{self, EXAMPLES},
+    light_types::{light::Light, shadow_map::ShadowSettings},
+    shader_library::ShaderLibrary,
+    state::{RenderOptions, State},
+    vertex_types::instance::Instance,
+};
+
+// The scene State starts with: the textured pentagon, the lit cube on the floor,
+// the point light's gizmo and the sun's shadows.
+#[test]
+fn default_scene() {
+    let Some(frame) = render_headless(256, 256, |_| {}) else {
+        return;
+    };
+    assert_matches_golden("default_scene", &frame, &Tolerance::default());
+}
+
+// A width that isn't a multiple of 64 pixels needs padded rows on readback, and
+// the camera has to follow the non-square aspect ratio.
+#[test]
+fn default_scene_wide() {
+    let Some(frame) = render_headless(300, 200, |_| {}) else {
+        return;
+    };
+    assert_matches_golden("default_scene_wide", &frame, &Tolerance::default());
+}
+
+// Frames are only read back from 8 bit RGBA and BGRA targets. Any other format
+// is turned down before a frame is drawn for it.
+#[test]
+fn capturing_an_unsupported_format() {
+    let Ok(mut state) = pollster::block_on(State::new_headless(
+        64,
+        64,
+        wgpu::TextureFormat::Rgba16Float,
+    )) else {
+        // Not every adapter can render to it, which is fine for this test
+        return;
+    };
+    state.update(std::time::Duration::ZERO);
+
+    let error = state.capture().unwrap_err();
+    assert!(format!("{error}").contains("Rgba16Float"), "{error}");
+    assert_eq!(state.cull_stats(), CullStats::default());
+}
+
+// A 3x3 grid of scaled and rotated pentagons drawn with a single instanced draw call.
+#[test]
+fn instanced_pentagons() {
+    let instances = (0..9)
+        .map(|i| Instance {
+            position: cgmath::Vector3::new(
+                (i % 3) as f32 * 0.6 - 0.6,
+                (i / 3) as f32 * 0.6 - 0.6,
+                0.0,
+            ),
+            rotation: cgmath::Quaternion::from_angle_z(cgmath::Deg(i as f32 * 20.0)),
+            scale: cgmath::Vector3::new(0.5, 0.5, 0.5),
+        })
+        .collect::<Vec<_>>();
+
+    let Some(frame) = render_headless(256, 256, |state| state.set_instances(&instances)) else {
+        return;
+    };
+    assert_matches_golden("instanced_pentagons", &frame, &Tolerance::default());
+}
+
+// Two overlapping pentagons, the nearer one drawn first
+fn near_then_far() -> [Instance; 2] {
+    [
+        Instance {
+            position: cgmath::Vector3::new(0.0, 0.0, 0.5),
+            ..Default::default()
+        },
+        Instance {
+            position: cgmath::Vector3::new(0.2, 0.1, -0.5),
+            scale: cgmath::Vector3::new(1.5, 1.5, 1.5),
+            ..Default::default()
+        },
+    ]
+}
+
+// The depth test keeps the nearer pentagon in front even though the one behind
+// it is drawn afterwards, so the order they're drawn in makes no difference
+#[test]
+fn nearer_geometry_wins_whatever_the_order() {
+    let instances = near_then_far();
+    let Some(frame) = render_headless(256, 256, |state| state.set_instances(&instances)) else {
+        return;
+    };
+    assert_matches_golden("depth_nearer_drawn_first", &frame, &Tolerance::default());
+
+    let reversed = [instances[1], instances[0]];
+    let frame_reversed = render_headless(256, 256, |state| state.set_instances(&reversed)).unwrap();
+    assert!(compare(&frame_reversed, &frame, &Tolerance::default()).passes(&Tolerance::default()));
+}
+
+// Greater keeps whatever is furthest away. That only draws anything at all if the
+// depth buffer is cleared to 0.0 for it, rather than the usual 1.0.
+#[test]
+fn depth_compare_can_be_changed() {
+    let instances = near_then_far();
+    let render = |compare_function, instances: &[Instance]| {
+        render_headless(256, 256, |state| {
+            state.set_instances(instances);
+            state.set_depth_compare(compare_function);
+        })
+    };
+    let Some(less) = render(wgpu::CompareFunction::Less, &instances) else {
+        return;
+    };
+    let greater = render(wgpu::CompareFunction::Greater, &instances).unwrap();
+    let greater_reversed = render(
+        wgpu::CompareFunction::Greater,
+        &[instances[1], instances[0]],
+    )
+    .unwrap();
+    let cleared_only = render_headless(256, 256, |state| state.set_instances(&[])).unwrap();
+
+    let same = |a: &RgbaImage, b: &RgbaImage| {
+        compare(a, b, &Tolerance::default()).passes(&Tolerance::default())
+    };
+    assert!(
+        !same(&greater, &less),
+        "Greater should keep the far pentagon in front"
+    );
+    assert!(
+        same(&greater, &greater_reversed),
+        "Greater should depend on depth, not the order things are drawn in"
+    );
+    // Cleared to 1.0, nothing could be further away than the clear value and the
+    // pentagons would vanish
+    assert!(
+        !same(&greater, &cleared_only),
+        "nothing passed the Greater depth test"
+    );
+    assert_matches_golden("depth_compare_greater", &greater, &Tolerance::default());
+}
+
+// More lights than the uniform fallback holds, of every kind, all accumulated in
+// one pass. Each point and spot light also gets its own gizmo.
+#[test]
+fn many_lights() {
+    let mut lights = (0..20)
+        .map(|i| {
+            let angle = i as f32 / 20.0 * std::f32::consts::TAU;
+            let color = [
+                (i % 3 == 0) as u32 as f32,
+                (i % 3 == 1) as u32 as f32,
+                (i % 3 == 2) as u32 as f32,
+            ];
+            Light::point(
+                (0.9 + angle.cos() * 1.2, 0.8, -1.0 + angle.sin() * 1.2).into(),
+                color,
+                0.4,
+                2.0,
+            )
+        })
+        .collect::<Vec<_>>();
+    lights.push(Light::spot(
+        (0.9, 0.0, 1.0).into(),
+        (0.0, 0.0, -1.0).into(),
+        cgmath::Deg(10.0),
+        cgmath::Deg(20.0),
+        [1.0, 1.0, 0.0],
+        3.0,
+        5.0,
+    ));
+    lights.push(Light::directional(
+        (0.0, -1.0, 0.0).into(),
+        [0.2, 0.2, 0.2],
+        1.0,
+    ));
+
+    let Some(frame) = render_headless(256, 256, |state| state.set_lights(&lights)) else {
+        return;
+    };
+    assert_matches_golden("many_lights", &frame, &Tolerance::default());
+}
+
+// The uniform array WebGL2 falls back to holds fewer lights, but as long as they
+// fit it lights the scene just the same as the storage buffer. It's forced here,
+// since most adapters (including the one CI renders with) have storage buffers.
+#[test]
+fn uniform_light_fallback() {
+    let mut lights = (0..12)
+        .map(|i| {
+            let angle = i as f32 / 12.0 * std::f32::consts::TAU;
+            let color = [
+                (i % 3 == 0) as u32 as f32,
+                (i % 3 == 1) as u32 as f32,
+                (i % 3 == 2) as u32 as f32,
+            ];
+            Light::point(
+                (0.9 + angle.cos() * 1.2, 0.8, -1.0 + angle.sin() * 1.2).into(),
+                color,
+                0.6,
+                2.0,
+            )
+        })
+        .collect::<Vec<_>>();
+    lights.push(Light::spot(
+        (0.9, 0.0, 1.0).into(),
+        (0.0, 0.0, -1.0).into(),
+        cgmath::Deg(10.0),
+        cgmath::Deg(20.0),
+        [1.0, 1.0, 0.0],
+        3.0,
+        5.0,
+    ));
+    lights.push(Light::directional(
+        (0.5, -1.0, 0.6).into(),
+        [0.4, 0.4, 0.4],
+        1.0,
+    ));
+
+    let render = |options: &RenderOptions| {
+        let mut state = headless_state_with(256, 256, options)?;
+        state.set_lights(&lights);
+        state.update(std::time::Duration::ZERO);
+        Some((state.storage_buffer_lights(), state.capture().unwrap()))
+    };
+    let Some((_, storage)) = render(&RenderOptions::default()) else {
+        return;
+    };
+    let (uses_storage, uniform) = render(&RenderOptions {
+        storage_buffer_lights: false,
+        ..Default::default()
+    })
+    .unwrap();
+    assert!(!uses_storage);
+
+    assert_matches_golden("uniform_light_fallback", &uniform, &Tolerance::default());
+    let tolerance = Tolerance::default();
+    let comparison = compare(&uniform, &storage, &tolerance);
+    assert!(
+        comparison.passes(&tolerance),
+        "{} pixels differ from the storage buffer's, mean error {:.3}",
+        comparison.failing_pixels,
+        comparison.mean_error
+    );
+}
+
+// A sun low enough to throw a long shadow, into a shadow map so small that the
+// PCF filtering shows along its edges.
+#[test]
+fn low_resolution_shadow() {
+    let Some(frame) = render_headless(256, 256, |state| {
+        state.set_lights(&[Light::directional(
+            (1.0, -0.6, 0.2).into(),
+            [1.0, 1.0, 1.0],
+            1.0,
+        )]);
+        state.set_shadow_settings(ShadowSettings {
+            resolution: 128,
+            ..Default::default()
+        });
+    }) else {
+        return;
+    };
+    assert_matches_golden("low_resolution_shadow", &frame, &Tolerance::default());
+}
+
+// The pentagon casts a shadow too, even though it has a different vertex type
+// from the cube and the floor. It's tilted back over the floor so the sun
+// lands the shadow on it, next to the cube's.
+#[test]
+fn pentagon_shadow() {
+    let pentagon = Instance {
+        position: cgmath::Vector3::new(-0.5, -0.2, -1.0),
+        rotation: cgmath::Quaternion::from_angle_x(cgmath::Deg(-60.0)),
+        scale: cgmath::Vector3::new(0.8, 0.8, 0.8),
+    };
+
+    let Some(frame) = render_headless(256, 256, |state| state.set_instances(&[pentagon])) else {
+        return;
+    };
+    assert_matches_golden("pentagon_shadow", &frame, &Tolerance::default());
+}
+
+// Switching shadows off rebuilds the lit shader without them, and switching them
+// back on gets the shadow back exactly.
+#[test]
+fn shadows_can_be_switched_off() {
+    let Some(mut state) = headless_state(256, 256) else {
+        return;
+    };
+    state.set_lights(&[Light::directional(
+        (1.0, -0.6, 0.2).into(),
+        [1.0, 1.0, 1.0],
+        1.0,
+    )]);
+    state.set_shadow_settings(ShadowSettings {
+        resolution: 128,
+        ..Default::default()
+    });
+
+    let shadowed = state.capture().unwrap();
+    assert_matches_golden("low_resolution_shadow", &shadowed, &Tolerance::default());
+
+    state.set_shadows(false);
+    let unshadowed = state.capture().unwrap();
+    assert!(compare(&unshadowed, &shadowed, &Tolerance::default()).failing_pixels > 0);
+
+    state.set_shadows(true);
+    assert!(state.capture().unwrap() == shadowed);
+}
+
+// A flat, CAD style view of the scene. Reverse-Z flips the depth test along with
+// the depth range, so it should look just like it would without it.
+#[test]
+fn orthographic_reverse_z() {
+    let Some(frame) = render_headless(300, 200, |state| {
+        state
+            .set_projection(Projection::Orthographic {
+                size: OrthographicSize::Extent(1.5),
+                znear: 0.1,
+                zfar: 20.0,
+            })
+            .unwrap();
+        // Turned down, leaving the projection above in place
+        let no_far_plane = Projection::Orthographic {
+            size: OrthographicSize::Extent(1.5),
+            znear: 0.1,
+            zfar: f32::INFINITY,
+        };
+        assert!(state.set_projection(no_far_plane).is_err());
+        state.set_reverse_z(true);
+    }) else {
+        return;
+    };
+    assert_matches_golden("orthographic_reverse_z", &frame, &Tolerance::default());
+}
+
+// Culling can only ever skip things that weren't going to be seen anyway
+#[test]
+fn culling_does_not_change_the_picture() {
+    // A row of pentagons running from well off the left of the screen to well off the right
+    let instances = (0..15)
+        .map(|i| Instance {
+            position: cgmath::Vector3::new(i as f32 - 7.0, 0.0, 0.0),
+            rotation: cgmath::Quaternion::from_angle_z(cgmath::Deg(i as f32 * 10.0)),
+            ..Default::default()
+        })
+        .collect::<Vec<_>>();
+
+    let Some(mut state) = headless_state(256, 256) else {
+        return;
+    };
+    state.set_instances(&instances);
+    state.update(std::time::Duration::ZERO);
+
+    let culled = state.capture().unwrap();
+    let stats = state.cull_stats();
+    // The cube and floor are both in view
+    assert_eq!(stats.drawn + stats.culled, 17);
+    assert!(stats.culled >= 8, "{stats:?}");
+    assert!(stats.drawn >= 5, "{stats:?}");
+
+    state.set_frustum_culling(false);
+    let unculled = state.capture().unwrap();
+    assert_eq!(
+        state.cull_stats(),
+        CullStats {
+            drawn: 17,
+            culled: 0
+        }
+    );
+
+    assert!(culled == unculled, "culling changed what was drawn");
+}
+
+// Each copy of a glTF mesh is culled on its own. A narrow view takes in the
+// triangle at the origin but not the one its parent node moves up and away.
+#[test]
+fn gltf_meshes_are_culled() {
+    let Some(mut state) = headless_state(256, 256) else {
+        return;
+    };
+    state.set_instances(&[]);
+    state
+        .set_projection(Projection::Orthographic {
+            size: OrthographicSize::Extent(0.5),
+            znear: 0.1,
+            zfar: 20.0,
+        })
+        .unwrap();
+    state.update(std::time::Duration::ZERO);
+    state.capture().unwrap();
+    let without = state.cull_stats();
+
+    state
+        .add_gltf_scene(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/triangle.gltf"
+        ))
+        .unwrap();
+    let culled = state.capture().unwrap();
+    let with = state.cull_stats();
+    assert_eq!(with.drawn, without.drawn + 1, "{with:?}");
+    assert_eq!(with.culled, without.culled + 1, "{with:?}");
+
+    state.set_frustum_culling(false);
+    let unculled = state.capture().unwrap();
+    assert_eq!(state.cull_stats().drawn, with.drawn + with.culled);
+    assert!(culled == unculled, "culling changed what was drawn");
+}
+
+#[test]
+fn compare_flags_pixels_outside_tolerance() {
+    let expected = RgbaImage::from_pixel(4, 4, Rgba([10, 20, 30, 255]));
+    let mut actual = expected.clone();
+    actual.put_pixel(1, 1, Rgba([12, 20, 30, 255]));
+    actual.put_pixel(2, 2, Rgba([10, 20, 90, 255]));
+
+    let comparison = compare(&actual, &expected, &Tolerance::default());
+
+    assert_eq!(comparison.failing_pixels, 1);
+    assert_eq!(*comparison.diff.get_pixel(2, 2), Rgba([255, 0, 0, 255]));
+    assert_ne!(*comparison.diff.get_pixel(1, 1), Rgba([255, 0, 0, 255]));
+    assert!((comparison.mean_error - 62.0 / 64.0).abs() < 1e-9);
+}
+
+// Every pixel a little off is within per_channel, but the mean error still fails it
+#[test]
+fn compare_fails_on_mean_error() {
+    let expected = RgbaImage::from_pixel(4, 4, Rgba([10, 20, 30, 255]));
+    let actual = RgbaImage::from_pixel(4, 4, Rgba([12, 22, 32, 255]));
+
+    let comparison = compare(&actual, &expected, &Tolerance::default());
+
+    assert_eq!(comparison.failing_pixels, 0);
+    assert!((comparison.mean_error - 1.5).abs() < 1e-9);
+    assert!(!comparison.passes(&Tolerance::default()));
+    assert!(comparison.passes(&Tolerance {
+        max_mean_error: 2.0,
+        ..Tolerance::default()
+    }));
+}
+
+// tests/fixtures/triangle.gltf in place of the pentagon: one copy of the triangle
+// scaled up at the origin and one moved up and to the right by its parent node,
+// both tinted by the material's base color factor and lit like the cube. The
+// view is pulled out far enough to see all of both.
+#[test]
+fn gltf_triangle() {
+    let Some(frame) = render_headless(256, 256, |state| {
+        state.set_instances(&[]);
+        state
+            .set_projection(Projection::Orthographic {
+                size: OrthographicSize::Extent(3.0),
+                znear: 0.1,
+                zfar: 20.0,
+            })
+            .unwrap();
+        // Shining the same way as the camera looks, onto the front of the triangles
+        state.set_lights(&[Light::directional(
+            (0.0, -0.5, -1.0).into(),
+            [1.0, 1.0, 1.0],
+            1.0,
+        )]);
+        state
+            .add_gltf_scene(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/fixtures/triangle.gltf"
+            ))
+            .unwrap();
+    }) else {
+        return;
+    };
+    assert_matches_golden("gltf_triangle", &frame, &Tolerance::default());
+}
+
+// tests/fixtures/emissive.gltf is the same triangles in a black material that
+// glows with orange.png. The emissive texture scales the emissive factor of
+// [1, 1, 1], so they come out orange rather than white.
+#[test]
+fn gltf_emissive() {
+    let Some(frame) = render_headless(256, 256, |state| {
+        state.set_instances(&[]);
+        state
+            .set_projection(Projection::Orthographic {
+                size: OrthographicSize::Extent(3.0),
+                znear: 0.1,
+                zfar: 20.0,
+            })
+            .unwrap();
+        state
+            .add_gltf_scene(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/fixtures/emissive.gltf"
+            ))
+            .unwrap();
+    }) else {
+        return;
+    };
+    assert_matches_golden("gltf_emissive", &frame, &Tolerance::default());
+    // Inside the larger triangle, the same color as orange.png
+    let [r, g, b, _] = frame.get_pixel(140, 115).0;
+    assert!(r == 255 && g.abs_diff(128) <= 2 && b == 0, "{r}, {g}, {b}");
+}
+
+// A solid color on each face of the cubemap. The camera looks down at the scene,
+// so the sky shows the -Z face with the top (+Y) and bottom (-Y) faces above and
+// below it.
+#[test]
+fn skybox_faces() {
+    let colors = [
+        [255, 0, 0],
+        [0, 255, 255],
+        [0, 255, 0],
+        [255, 0, 255],
+        [0, 0, 255],
+        [255, 255, 0],
+    ];
+    let faces = colors.map(|[r, g, b]| {
+        image::DynamicImage::ImageRgba8(RgbaImage::from_pixel(4, 4, Rgba([r, g, b, 255])))
+    });
+
+    let Some(frame) = render_headless(256, 256, |state| state.set_skybox_faces(&faces).unwrap())
+    else {
+        return;
+    };
+    assert_matches_golden("skybox_faces", &frame, &Tolerance::default());
+}
+
+// A panorama with a bright band along the horizon, converted on the GPU, seen
+// through an infinite reverse-Z projection whose far plane can't be unprojected.
+#[test]
+fn skybox_equirectangular() {
+    let panorama = image::Rgb32FImage::from_fn(64, 32, |x, y| {
+        let longitude = x as f32 / 64.0;
+        let latitude = 1.0 - y as f32 / 16.0;
+        let horizon = 2.0 * (1.0 - latitude.abs() * 4.0).max(0.0);
+        image::Rgb([
+            longitude + horizon,
+            0.2 + horizon,
+            latitude.max(0.0) + horizon,
+        ])
+    });
+
+    let Some(frame) = render_headless(256, 256, |state| {
+        state
+            .set_projection(Projection::Perspective {
+                fovy: cgmath::Deg(60.0),
+                znear: 0.1,
+                zfar: f32::INFINITY,
+            })
+            .unwrap();
+        state.set_reverse_z(true);
+        state
+            .set_skybox_equirectangular(&image::DynamicImage::ImageRgb32F(panorama), 32)
+            .unwrap();
+    }) else {
+        return;
+    };
+    assert_matches_golden("skybox_equirectangular", &frame, &Tolerance::default());
+}
+
+// Shaders read from a directory are rebuilt when they're saved. One that doesn't
+// compile leaves the last good pipeline in place, so the frame doesn't change.
+#[test]
+fn shader_hot_reload() {
+    let resources = concat!(env!("CARGO_MANIFEST_DIR"), "/src/types/resources");
+    let dir = std::path::PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("shader_hot_reload");
+    std::fs::create_dir_all(&dir).unwrap();
+    for entry in std::fs::read_dir(resources).unwrap() {
+        let path = entry.unwrap().path();
+        std::fs::copy(&path, dir.join(path.file_name().unwrap())).unwrap();
+    }
+    // Later than the copies, however coarse the file system's timestamps are
+    let save = |name: &str, source: &str| {
+        let path = dir.join(name);
+        std::fs::write(&path, source).unwrap();
+        let later = std::time::SystemTime::now() + std::time::Duration::from_secs(10);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+    };
+
+    let Some(mut state) = headless_state(256, 256) else {
+        return;
+    };
+    let mut shaders = ShaderLibrary::from_dir(&dir);
+    shaders.poll_interval = std::time::Duration::ZERO;
+    state.set_shader_library(shaders);
+    state.update(std::time::Duration::ZERO);
+    assert_matches_golden(
+        "default_scene",
+        &state.capture().unwrap(),
+        &Tolerance::default(),
+    );
+
+    let lit = std::fs::read_to_string(dir.join("lit_shader.wgsl")).unwrap();
+    save(
+        "lit_shader.wgsl",
+        &lit.replace("fn fs_main", "fn fs_main(oops"),
+    );
+    state.update(std::time::Duration::ZERO);
+    assert_matches_golden(
+        "default_scene",
+        &state.capture().unwrap(),
+        &Tolerance::default(),
+    );
+
+    let instanced = std::fs::read_to_string(dir.join("instanced_shader.wgsl")).unwrap();
+    save(
+        "instanced_shader.wgsl",
+        &instanced.replace(
+            "return textureSample(t_diffuse, s_diffuse, in.tex_coords);",
+            "return vec4<f32>(1.0, 0.0, 0.0, 1.0);",
+        ),
+    );
+    state.update(std::time::Duration::ZERO);
+    let frame = state.capture().unwrap();
+    // The middle of the pentagon
+    assert_eq!(frame.get_pixel(110, 115).0, [255, 0, 0, 255]);
+}
+
+// Each of the tutorial's stages, drawn in place of the scene
+#[test]
+fn examples() {
+    for (index, example) in EXAMPLES
+        .iter()
+        .enumerate()
+        .filter(|(_, example)| !example.is_scene())
+    {
+        let Some(frame) = render_headless(256, 256, |state| state.set_example(index)) else {
+            return;
+        };
+        assert_matches_golden(
+            &format!("example_{}", example.name),
+            &frame,
+            &Tolerance::default(),
+        );
+    }
+}
+
+// Going back to the scene drops the example, so it's drawn as if there never was one
+#[test]
+fn switching_back_to_the_scene() {
+    let Some(frame) = render_headless(256, 256, |state| {
+        state.set_example(examples::find("camera").unwrap());
+        state.set_example(examples::find("scene").unwrap());
+    }) else {
+        return;
+    };
+    assert_matches_golden("default_scene", &frame, &Tolerance::default());
+}
+
+// The clear_color example takes the color from where the cursor is
+#[test]
+fn example_input() {
+    let Some(frame) = render_headless(256, 256, |state| {
+        state.set_example(examples::find("clear_color").unwrap());
+        let position = winit::dpi::PhysicalPosition::new(64.0, 192.0);
+        assert!(state.input(&winit::event::WindowEvent::CursorMoved {
+            device_id: unsafe { winit::event::DeviceId::dummy() },
+            position
+        }));
+    }) else {
+        return;
+    };
+    // (0.25, 0.75, 0.1875) in sRGB
+    let [r, g, b, a] = frame.get_pixel(10, 10).0;
+    assert!(
+        r.abs_diff(137) <= 1 && g.abs_diff(225) <= 1 && b.abs_diff(119) <= 1 && a == 255,
+        "got {:?}",
+        [r, g, b, a]
+    );
+}
+
+// MSAA smooths the pentagons' edges and leaves everything else as it was
+#[test]
+fn msaa() {
+    let Some(mut state) = headless_state(256, 256) else {
+        return;
+    };
+    if !state.sample_counts().contains(&4) {
+        eprintln!("skipping test, the adapter can't do 4x MSAA");
+        return;
+    }
+    state.set_sample_count(4).unwrap();
+    state.update(std::time::Duration::ZERO);
+    let frame = state.capture().unwrap();
+    assert_matches_golden("msaa_4x", &frame, &Tolerance::default());
+
+    let without = render_headless(256, 256, |_| {}).unwrap();
+    let comparison = compare(&frame, &without, &Tolerance::default());
+    assert!(
+        comparison.failing_pixels > 0,
+        "the edges should have been smoothed"
+    );
+    assert!(
+        comparison.failing_fraction() < 0.05,
+        "only the edges should change, {} pixels did",
+        comparison.failing_pixels
+    );
+
+    // Counts the adapter can't do are turned down rather than crashing later
+    assert!(state.set_sample_count(3).is_err());
+    assert_eq!(state.sample_count(), 4);
+}
@@ -0,0 +1,98 @@
+use std::path::Path;
+
+use wgpu_ex::types::model::parse_obj;
+
+fn no_files(path: &Path) -> anyhow::Result<Vec<u8>> {
+    anyhow::bail!("unexpected file {}", path.display())
+}
+
+const QUAD: &str = "
+o quad
+v 0.0 0.0 0.0
+v 1.0 0.0 0.0
+v 1.0 1.0 0.0
+v 0.0 1.0 0.0
+vt 0.0 0.0
+vt 1.0 0.0
+vt 1.0 1.0
+vt 0.0 1.0
+vn 0.0 0.0 1.0
+f 1/1/1 2/2/1 3/3/1 4/4/1
+";
+
+#[test]
+fn quads_are_triangulated() {
+    let (meshes, materials) = parse_obj(QUAD.as_bytes(), no_files).unwrap();
+
+    assert_eq!(meshes.len(), 1);
+    assert!(materials.is_empty());
+    assert_eq!(meshes[0].name, "quad");
+    assert_eq!(meshes[0].vertices.len(), 4);
+    assert_eq!(meshes[0].indices.len(), 6);
+    // v is flipped to match wgpu's texture coordinates
+    assert_eq!(meshes[0].vertices[2].tex_coords, [1.0, 0.0]);
+}
+
+#[test]
+fn shared_vertices_are_deduplicated() {
+    let obj = "
+v 0.0 0.0 0.0
+v 1.0 0.0 0.0
+v 1.0 1.0 0.0
+v 0.0 1.0 0.0
+vt 0.0 0.0
+vt 0.5 0.5
+f 1/1 2/1 3/1
+f 1/1 3/1 4/1
+f 1/2 3/1 4/1
+";
+    let (meshes, _) = parse_obj(obj.as_bytes(), no_files).unwrap();
+
+    // 1/1 and 3/1 are shared between the first two faces, 1/2 is a new combination
+    assert_eq!(meshes[0].indices.len(), 9);
+    assert_eq!(meshes[0].vertices.len(), 5);
+}
+
+#[test]
+fn missing_normals_are_computed() {
+    let obj = "
+v 0.0 0.0 0.0
+v 1.0 0.0 0.0
+v 0.0 1.0 0.0
+f 1 2 3
+";
+    let (meshes, _) = parse_obj(obj.as_bytes(), no_files).unwrap();
+
+    for vertex in &meshes[0].vertices {
+        assert_eq!(vertex.normal, [0.0, 0.0, 1.0]);
+    }
+}
+
+#[test]
+fn materials_are_loaded_from_mtl_files() {
+    let obj = format!("mtllib quad.mtl\nusemtl brick\n{QUAD}");
+    let mtl = "newmtl brick\nKd 1.0 1.0 1.0\nmap_Kd brick.png\n";
+
+    let (meshes, materials) = parse_obj(obj.as_bytes(), |path| {
+        assert_eq!(path, Path::new("quad.mtl"));
+        Ok(mtl.as_bytes().to_vec())
+    })
+    .unwrap();
+
+    assert_eq!(materials.len(), 1);
+    assert_eq!(materials[0].name, "brick");
+    assert_eq!(materials[0].diffuse_texture.as_deref(), Some("brick.png"));
+    assert_eq!(meshes[0].material, Some(0));
+}
+
+#[test]
+fn malformed_files_are_errors() {
+    let out_of_range = "v 0.0 0.0 0.0\nv 1.0 0.0 0.0\nf 1 2 7\n";
+    assert!(parse_obj(out_of_range.as_bytes(), no_files).is_err());
+
+    let bad_number = "v 0.0 zero 0.0\n";
+    assert!(parse_obj(bad_number.as_bytes(), no_files).is_err());
+
+    let missing_mtl = format!("mtllib missing.mtl\nusemtl brick\n{QUAD}");
+    assert!(parse_obj(missing_mtl.as_bytes(), no_files).is_err());
+}
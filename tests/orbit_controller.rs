@@ -0,0 +1,97 @@
+use cgmath::{InnerSpace, MetricSpace};
+use wgpu_ex::types::camera_types::{
+    camera::{Camera, Projection},
+    orbit_controller::OrbitController,
+};
+
+fn camera() -> Camera {
+    Camera {
+        eye: (0.0, 0.0, 4.0).into(),
+        target: (0.0, 0.0, 0.0).into(),
+        up: cgmath::Vector3::unit_y(),
+        aspect: 1.0,
+        projection: Projection::Perspective {
+            fovy: cgmath::Deg(45.0),
+            znear: 0.1,
+            zfar: 100.0,
+        },
+        reverse_z: false,
+    }
+}
+
+fn assert_close(a: f32, b: f32) {
+    assert!((a - b).abs() < 1e-4, "{a} != {b}");
+}
+
+#[test]
+fn rotating_keeps_distance_to_target() {
+    let mut camera = camera();
+    let mut controller = OrbitController::new(1.0, 10.0);
+
+    controller.rotate(100.0, 50.0);
+    controller.update_camera(&mut camera);
+
+    assert_close(camera.eye.distance(camera.target), 4.0);
+    assert!(camera.eye.x.abs() > 0.1 && camera.eye.y > 0.1);
+    assert_eq!(camera.target, (0.0, 0.0, 0.0).into());
+}
+
+#[test]
+fn pitch_is_clamped_short_of_up() {
+    let mut camera = camera();
+    let mut controller = OrbitController::new(1.0, 10.0);
+
+    // Far more than enough to go over the top
+    controller.rotate(0.0, 10_000.0);
+    controller.update_camera(&mut camera);
+
+    let direction = (camera.eye - camera.target).normalize();
+    assert_close(direction.dot(camera.up).asin(), controller.max_pitch.0);
+
+    // Still on the same side of the target, it didn't flip over
+    assert!(camera.eye.z > 0.0);
+}
+
+#[test]
+fn zoom_stays_within_limits() {
+    let mut camera = camera();
+    let mut controller = OrbitController::new(1.0, 10.0);
+
+    controller.zoom(1.0);
+    controller.update_camera(&mut camera);
+    assert_close(camera.eye.distance(camera.target), 4.0 * 0.9);
+
+    controller.zoom(1000.0);
+    controller.update_camera(&mut camera);
+    assert_close(camera.eye.distance(camera.target), 1.0);
+
+    controller.zoom(-1000.0);
+    controller.update_camera(&mut camera);
+    assert_close(camera.eye.distance(camera.target), 10.0);
+}
+
+#[test]
+fn pan_moves_eye_and_target_together() {
+    let mut camera = camera();
+    let mut controller = OrbitController::new(1.0, 10.0);
+
+    controller.pan(100.0, 0.0);
+    controller.update_camera(&mut camera);
+
+    // Dragging right moves the scene right, so the camera goes left
+    assert!(camera.target.x < 0.0);
+    assert_close(camera.eye.x, camera.target.x);
+    assert_close(camera.eye.distance(camera.target), 4.0);
+}
+
+#[test]
+fn no_input_leaves_camera_alone() {
+    let mut camera = camera();
+    camera.eye = (0.3, 0.2, 0.01).into();
+    let mut controller = OrbitController::new(1.0, 10.0);
+
+    controller.update_camera(&mut camera);
+
+    // Closer than min_distance, but only mouse input gets clamped
+    assert_eq!(camera.eye, (0.3, 0.2, 0.01).into());
+}
@@ -0,0 +1,232 @@
+mod common;
+
+use common::device;
+use wgpu_ex::types::pipeline_builder::PipelineBuilder;
+
+// A triangle made up in the vertex shader, written to two targets at once
+const SHADER: &str = "
+override brightness: f32 = 1.0;
+@id(7) override alpha: f32 = 1.0;
+
+struct Output {
+    @location(0) color: vec4<f32>,
+    @location(1) normal: vec4<f32>,
+}
+
+@vertex
+fn vertex(@builtin(vertex_index) i: u32) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(f32(i) - 1.0, f32(i & 1u), 0.5, 1.0);
+}
+
+@fragment
+fn fragment() -> Output {
+    return Output(vec4<f32>(brightness, brightness, brightness, alpha), vec4<f32>(0.0, 0.0, 1.0, 1.0));
+}
+";
+
+fn builder() -> PipelineBuilder<'static> {
+    PipelineBuilder::new("test", SHADER)
+        .vertex_entry_point("vertex")
+        .fragment_entry_point("fragment")
+        .color_target(wgpu::TextureFormat::Rgba8Unorm)
+        .color_target(wgpu::TextureFormat::Rgba16Float)
+}
+
+fn build_error(device: &wgpu::Device, builder: PipelineBuilder) -> String {
+    format!(
+        "{:#}",
+        builder
+            .build(device)
+            .expect_err("the pipeline shouldn't build")
+    )
+}
+
+#[test]
+fn everything_can_be_set() {
+    let Some((device, _)) = device() else { return };
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+
+    builder()
+        .topology(wgpu::PrimitiveTopology::TriangleStrip)
+        .strip_index_format(wgpu::IndexFormat::Uint16)
+        .front_face(wgpu::FrontFace::Cw)
+        .cull_mode(None)
+        .depth_stencil(wgpu::DepthStencilState {
+            format: wgpu::TextureFormat::Depth32Float,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::LessEqual,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        })
+        .sample_count(4)
+        .alpha_to_coverage(true)
+        .constant("brightness", 0.5)
+        .constant("7", 0.25)
+        .build(&device)
+        .unwrap();
+
+    assert!(pollster::block_on(device.pop_error_scope()).is_none());
+}
+
+#[test]
+fn missing_features_are_errors() {
+    let Some((device, _)) = device() else { return };
+    if device
+        .features()
+        .contains(wgpu::Features::POLYGON_MODE_LINE)
+    {
+        return;
+    }
+
+    let error = build_error(&device, builder().polygon_mode(wgpu::PolygonMode::Line));
+    assert!(
+        error.contains("couldn't build the test pipeline"),
+        "{error}"
+    );
+    assert!(
+        error.contains("PolygonMode::Line needs Features::POLYGON_MODE_LINE"),
+        "{error}"
+    );
+}
+
+#[test]
+fn inconsistent_settings_are_errors() {
+    let Some((device, _)) = device() else { return };
+
+    for (builder, expected) in [
+        (
+            builder().strip_index_format(wgpu::IndexFormat::Uint32),
+            "strip topology",
+        ),
+        (
+            builder().no_fragment(),
+            "color targets need a fragment shader",
+        ),
+        (builder().sample_count(3), "isn't a power of two"),
+        (
+            builder().alpha_to_coverage(true),
+            "alpha to coverage needs multisampling",
+        ),
+        (
+            builder().fragment_entry_point("fs_main"),
+            "no fragment entry point called fs_main",
+        ),
+        (
+            builder().vertex_entry_point("vs_main"),
+            "no vertex entry point called vs_main",
+        ),
+        (
+            builder().constant("contrast", 2.0),
+            "no override called contrast",
+        ),
+        (
+            PipelineBuilder::new("test", SHADER)
+                .vertex_entry_point("vertex")
+                .fragment_entry_point("fragment"),
+            "no color targets or depth buffer",
+        ),
+    ] {
+        let error = build_error(&device, builder);
+        assert!(error.contains(expected), "{error}");
+    }
+}
+
+#[test]
+fn vertex_inputs_have_to_match_the_buffers() {
+    let Some((device, _)) = device() else { return };
+    let shader =
+        "@vertex fn vs_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+                      return vec4<f32>(position, 1.0);
+                  }";
+
+    let depth_only = || {
+        PipelineBuilder::new("test", shader)
+            .no_fragment()
+            .depth_stencil(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            })
+    };
+
+    let error = build_error(&device, depth_only());
+    assert!(
+        error.contains("vs_main reads @location(0) position: vec3<f32>"),
+        "{error}"
+    );
+
+    let attributes = wgpu::vertex_attr_array![0 => Float32x3];
+    let buffer = wgpu::VertexBufferLayout {
+        array_stride: 12,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &attributes,
+    };
+    depth_only().vertex_buffer(buffer).build(&device).unwrap();
+}
+
+#[test]
+fn color_targets_have_to_fit_the_fragment_outputs() {
+    let Some((device, _)) = device() else { return };
+    let shader =
+        "@vertex fn vs_main(@builtin(vertex_index) i: u32) -> @builtin(position) vec4<f32> {
+                      return vec4<f32>(f32(i) - 1.0, f32(i & 1u), 0.5, 1.0);
+                  }
+                  @fragment fn fs_main() -> @location(0) vec2<f32> {
+                      return vec2<f32>(1.0, 0.5);
+                  }
+                  @fragment fn fs_uint() -> @location(0) vec2<u32> {
+                      return vec2<u32>(1u, 2u);
+                  }";
+    let target = |format| PipelineBuilder::new("test", shader).color_target(format);
+    let blended = |format| wgpu::ColorTargetState {
+        format,
+        blend: Some(wgpu::BlendState::REPLACE),
+        write_mask: wgpu::ColorWrites::ALL,
+    };
+    let unwritten = wgpu::ColorTargetState {
+        format: wgpu::TextureFormat::R8Unorm,
+        blend: None,
+        write_mask: wgpu::ColorWrites::empty(),
+    };
+
+    // Formats that can't be blended still work as plain color targets
+    target(wgpu::TextureFormat::Rg8Uint)
+        .fragment_entry_point("fs_uint")
+        .build(&device)
+        .unwrap();
+    target(wgpu::TextureFormat::Rg8Unorm)
+        .color_target_state(unwritten)
+        .build(&device)
+        .unwrap();
+
+    for (builder, expected) in [
+        (
+            PipelineBuilder::new("test", shader)
+                .color_target_state(blended(wgpu::TextureFormat::R32Float)),
+            "R32Float can't be blended",
+        ),
+        (
+            PipelineBuilder::new("test", shader)
+                .fragment_entry_point("fs_uint")
+                .color_target_state(blended(wgpu::TextureFormat::Rg8Uint)),
+            "Rg8Uint can't be blended",
+        ),
+        (
+            target(wgpu::TextureFormat::Rg32Uint),
+            "writes @location(0) its result as a vec2<f32>, which doesn't fit the Rg32Uint",
+        ),
+        (
+            target(wgpu::TextureFormat::Rgba8Unorm),
+            "doesn't fit the Rgba8Unorm",
+        ),
+        (
+            target(wgpu::TextureFormat::Rg8Unorm).color_target(wgpu::TextureFormat::R8Unorm),
+            "doesn't write @location(1)",
+        ),
+    ] {
+        let error = build_error(&device, builder);
+        assert!(error.contains(expected), "{error}");
+    }
+}
@@ -0,0 +1,210 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use anyhow::anyhow;
+use wgpu_ex::types::{
+    light_types::light_manager::{LIGHTS_INCLUDE, LightManager},
+    preprocessor::{Preprocessor, ProcessedShader},
+};
+
+const RESOURCES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/types/resources");
+
+// Processes `name` with only the given files to include
+fn process(
+    files: &[(&str, &str)],
+    name: &str,
+    defines: &[(&str, &str)],
+) -> anyhow::Result<ProcessedShader> {
+    let files: HashMap<String, String> = files
+        .iter()
+        .map(|(n, s)| (n.to_string(), s.to_string()))
+        .collect();
+    let mut preprocessor = Preprocessor::new(move |file| {
+        files
+            .get(file)
+            .cloned()
+            .map(Cow::Owned)
+            .ok_or_else(|| anyhow!("no file {file}"))
+    });
+    for (define, value) in defines {
+        preprocessor.define(*define, *value);
+    }
+    preprocessor.process(name)
+}
+
+#[test]
+fn includes_are_pasted_in_once() {
+    let files = [
+        (
+            "main.wgsl",
+            "#include \"a.wgsl\"\n#include \"b.wgsl\"\nmain",
+        ),
+        ("a.wgsl", "#include \"b.wgsl\"\na"),
+        ("b.wgsl", "b"),
+    ];
+    let shader = process(&files, "main.wgsl", &[]).unwrap();
+
+    assert_eq!(shader.source, "b\na\nmain\n");
+    assert_eq!(shader.files(), ["main.wgsl", "a.wgsl", "b.wgsl"]);
+}
+
+#[test]
+fn include_cycles_are_errors() {
+    let files = [
+        ("main.wgsl", "#include \"a.wgsl\""),
+        ("a.wgsl", "#include \"b.wgsl\""),
+        ("b.wgsl", "#include \"a.wgsl\""),
+    ];
+    let error = format!("{:#}", process(&files, "main.wgsl", &[]).unwrap_err());
+
+    assert!(
+        error.contains("#include cycle: main.wgsl -> a.wgsl -> b.wgsl -> a.wgsl"),
+        "{error}"
+    );
+    assert!(error.contains("included from main.wgsl:1"), "{error}");
+}
+
+#[test]
+fn missing_includes_are_errors() {
+    let files = [("main.wgsl", "x\n#include \"missing.wgsl\"")];
+    let error = format!("{:#}", process(&files, "main.wgsl", &[]).unwrap_err());
+
+    assert!(
+        error.contains("missing.wgsl") && error.contains("main.wgsl:2"),
+        "{error}"
+    );
+}
+
+#[test]
+fn defines_replace_whole_words() {
+    let files = [(
+        "main.wgsl",
+        "#define GROUP 2\n@group(GROUP) var GROUPS: u32;\n#undef GROUP\nGROUP",
+    )];
+    let shader = process(&files, "main.wgsl", &[]).unwrap();
+
+    assert_eq!(shader.source, "@group(2) var GROUPS: u32;\nGROUP\n");
+}
+
+#[test]
+fn ifdef_keeps_one_side() {
+    let files = [(
+        "main.wgsl",
+        "#ifdef SHADOWS\nshadows\n#ifndef SOFT\nhard\n#endif\n#else\nno shadows\n#endif\nend",
+    )];
+
+    assert_eq!(
+        process(&files, "main.wgsl", &[("SHADOWS", "")])
+            .unwrap()
+            .source,
+        "shadows\nhard\nend\n"
+    );
+    assert_eq!(
+        process(&files, "main.wgsl", &[("SHADOWS", ""), ("SOFT", "")])
+            .unwrap()
+            .source,
+        "shadows\nend\n"
+    );
+    assert_eq!(
+        process(&files, "main.wgsl", &[]).unwrap().source,
+        "no shadows\nend\n"
+    );
+}
+
+#[test]
+fn skipped_lines_are_not_processed() {
+    // Neither the include nor the define happen
+    let files = [(
+        "main.wgsl",
+        "#ifdef NEVER\n#include \"missing.wgsl\"\n#define X 1\n#endif\nX",
+    )];
+
+    assert_eq!(process(&files, "main.wgsl", &[]).unwrap().source, "X\n");
+}
+
+#[test]
+fn unbalanced_conditions_are_errors() {
+    for (source, expected) in [
+        ("#ifdef A\nx", "main.wgsl:1: #ifdef without #endif"),
+        ("x\n#endif", "main.wgsl:2: #endif without #ifdef"),
+        (
+            "#ifdef A\n#else\n#else\n#endif",
+            "main.wgsl:3: second #else",
+        ),
+        ("#pragma once", "main.wgsl:1: unknown directive #pragma"),
+    ] {
+        let error = process(&[("main.wgsl", source)], "main.wgsl", &[])
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(expected), "{error}");
+    }
+}
+
+#[test]
+fn lines_map_back_to_their_files() {
+    let files = [
+        (
+            "main.wgsl",
+            "// main\n#include \"a.wgsl\"\n#ifdef A\n#endif\nlast",
+        ),
+        ("a.wgsl", "// a"),
+    ];
+    let shader = process(&files, "main.wgsl", &[]).unwrap();
+
+    assert_eq!(shader.origin(1), Some(("main.wgsl", 1)));
+    assert_eq!(shader.origin(2), Some(("a.wgsl", 1)));
+    assert_eq!(shader.origin(3), Some(("main.wgsl", 5)));
+    assert_eq!(shader.origin(4), None);
+}
+
+#[test]
+fn validation_errors_point_at_the_original_file_and_line() {
+    let files = [
+        (
+            "main.wgsl",
+            "#include \"camera.wgsl\"\n@vertex\nfn vs_main() -> @builtin(position) vec4<f32> {\n    return camera.missing;\n}",
+        ),
+        (
+            "camera.wgsl",
+            "struct Camera {\n    view_proj: mat4x4<f32>,\n}\n@group(0) @binding(0)\nvar<uniform> camera: Camera;",
+        ),
+    ];
+    let error = process(&files, "main.wgsl", &[])
+        .unwrap()
+        .validate()
+        .unwrap_err()
+        .to_string();
+
+    assert!(error.starts_with("main.wgsl:4:"), "{error}");
+}
+
+#[test]
+fn resource_shaders_are_valid() {
+    let load = |storage_buffer: bool| {
+        move |file: &str| -> anyhow::Result<Cow<'static, str>> {
+            match file {
+                LIGHTS_INCLUDE => Ok(LightManager::bindings_source(storage_buffer).into()),
+                _ => Ok(std::fs::read_to_string(format!("{RESOURCES}/{file}"))?.into()),
+            }
+        }
+    };
+
+    // Every shader that isn't a snippet for the others, with each feature on and off
+    for name in [
+        "camera_shader.wgsl",
+        "textured_shader.wgsl",
+        "instanced_shader.wgsl",
+        "lit_shader.wgsl",
+        "light_shader.wgsl",
+        "shadow_shader.wgsl",
+        "skybox_shader.wgsl",
+    ] {
+        for (storage_buffer, shadows) in [(false, false), (true, true)] {
+            let mut preprocessor = Preprocessor::new(load(storage_buffer));
+            if shadows {
+                preprocessor.define("SHADOWS", "");
+            }
+            let shader = preprocessor.process(name).unwrap();
+            shader.validate().unwrap_or_else(|e| panic!("{e}"));
+        }
+    }
+}
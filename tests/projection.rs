@@ -0,0 +1,111 @@
+use cgmath::{Deg, Vector4};
+use wgpu_ex::types::camera_types::camera::{OPENGL_TO_WGPU_MATRIX, OrthographicSize, Projection};
+
+// Depth in wgpu's clip space (after the divide by w) of a point `distance` in front of the camera
+fn depth(projection: &Projection, reverse_z: bool, distance: f32) -> f32 {
+    let clip = projection.matrix(1.0, reverse_z) * Vector4::new(0.0, 0.0, -distance, 1.0);
+    clip.z / clip.w
+}
+
+fn assert_close(a: f32, b: f32) {
+    assert!((a - b).abs() < 1e-4, "{a} != {b}");
+}
+
+#[test]
+fn perspective_depth_range() {
+    let projection = Projection::Perspective {
+        fovy: Deg(45.0),
+        znear: 0.1,
+        zfar: 100.0,
+    };
+    assert_close(depth(&projection, false, 0.1), 0.0);
+    assert_close(depth(&projection, false, 100.0), 1.0);
+    assert_close(depth(&projection, true, 0.1), 1.0);
+    assert_close(depth(&projection, true, 100.0), 0.0);
+}
+
+#[test]
+fn infinite_far_plane_never_clips() {
+    let projection = Projection::Perspective {
+        fovy: Deg(45.0),
+        znear: 0.1,
+        zfar: f32::INFINITY,
+    };
+    assert_close(depth(&projection, false, 0.1), 0.0);
+    assert_close(depth(&projection, true, 0.1), 1.0);
+
+    let far = depth(&projection, false, 1.0e6);
+    assert!(far > 0.999 && far <= 1.0);
+    let far = depth(&projection, true, 1.0e6);
+    assert!((0.0..0.001).contains(&far));
+}
+
+#[test]
+fn orthographic_depth_range_and_size() {
+    let projection = Projection::Orthographic {
+        size: OrthographicSize::Extent(2.0),
+        znear: 1.0,
+        zfar: 11.0,
+    };
+    assert_close(depth(&projection, false, 1.0), 0.0);
+    assert_close(depth(&projection, false, 6.0), 0.5);
+    assert_close(depth(&projection, true, 11.0), 0.0);
+
+    // Half the height is 2.0 whatever the distance
+    for distance in [1.0, 10.0] {
+        let clip = projection.matrix(1.0, false) * Vector4::new(0.0, 2.0, -distance, 1.0);
+        assert_close(clip.y / clip.w, 1.0);
+    }
+}
+
+#[test]
+fn orthographic_needs_a_far_plane() {
+    let projection = Projection::Orthographic {
+        size: OrthographicSize::Extent(2.0),
+        znear: 1.0,
+        zfar: f32::INFINITY,
+    };
+    let error = projection.validate().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "an orthographic projection needs a finite zfar, not inf"
+    );
+
+    // An infinite far plane is fine with perspective
+    let projection = Projection::Perspective {
+        fovy: Deg(45.0),
+        znear: 0.1,
+        zfar: f32::INFINITY,
+    };
+    assert!(projection.validate().is_ok());
+}
+
+#[test]
+fn orthographic_bounds_keep_aspect_ratio() {
+    let size = OrthographicSize::Bounds {
+        left: 0.0,
+        right: 4.0,
+        bottom: 0.0,
+        top: 2.0,
+    };
+
+    // Wider than the bounds, so they get wider too
+    assert_eq!(size.bounds(4.0), (-2.0, 6.0, 0.0, 2.0));
+    // Taller than the bounds
+    assert_eq!(size.bounds(1.0), (0.0, 4.0, -1.0, 3.0));
+
+    assert_eq!(
+        OrthographicSize::Extent(1.0).bounds(2.0),
+        (-2.0, 2.0, -1.0, 1.0)
+    );
+}
+
+// The shadow map's light projection comes from cgmath, so its depth is squeezed
+// from OpenGL's -1.0 to 1.0 into 0.0 to 1.0. w has to come through untouched.
+#[test]
+fn opengl_depth_is_mapped_onto_wgpu_depth() {
+    for (gl_z, wgpu_z) in [(-1.0, 0.0), (0.0, 0.5), (1.0, 1.0)] {
+        let clip = OPENGL_TO_WGPU_MATRIX * Vector4::new(0.3, -0.2, gl_z, 1.0);
+        assert_eq!(clip, Vector4::new(0.3, -0.2, wgpu_z, 1.0));
+    }
+}
@@ -0,0 +1,301 @@
+mod common;
+
+use common::device;
+use wgpu_ex::types::{
+    light_types::light_manager::{LIGHTS_INCLUDE, LightManager},
+    preprocessor::Preprocessor,
+    reflection::{ShaderReflection, merge_entries},
+    shader_library::{INSTANCED_SHADER, LIGHT_SHADER, LIT_SHADER, ShaderLibrary},
+    vertex_types::{
+        Vertex, instance::InstanceRaw, model_vertex::ModelVertex, textured_vertex::TexturedVertex,
+    },
+};
+
+fn uniform_buffer() -> wgpu::BindingType {
+    wgpu::BindingType::Buffer {
+        ty: wgpu::BufferBindingType::Uniform,
+        has_dynamic_offset: false,
+        min_binding_size: None,
+    }
+}
+
+// One of the built in shaders, with its includes resolved
+fn built_in(name: &str) -> ShaderReflection {
+    let library = ShaderLibrary::embedded();
+    let mut preprocessor = Preprocessor::new(|file| match file {
+        LIGHTS_INCLUDE => Ok(LightManager::bindings_source(true).into()),
+        _ => library.source(file),
+    });
+    preprocessor.define("SHADOWS", "");
+    preprocessor.process(name).unwrap().reflect().unwrap()
+}
+
+#[test]
+fn bindings_become_layout_entries() {
+    let shader = ShaderReflection::new(
+        "@group(0) @binding(2) var<storage, read> points: array<vec4<f32>>;
+         @group(0) @binding(0) var t_shadow: texture_depth_2d;
+         @group(0) @binding(1) var s_shadow: sampler_comparison;
+         @group(1) @binding(0) var t_sky: texture_cube<f32>;
+         @group(1) @binding(1) var<storage, read_write> counts: array<atomic<u32>>;
+
+         @vertex
+         fn vs_main(@builtin(vertex_index) i: u32) -> @builtin(position) vec4<f32> {
+             return points[i];
+         }
+
+         @fragment
+         fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+             let lit = textureSampleCompare(t_shadow, s_shadow, position.xy, position.z);
+             return vec4<f32>(lit);
+         }",
+    )
+    .unwrap();
+
+    let group_0 = shader.bind_group_layout_entries(0).unwrap();
+    assert_eq!(
+        group_0.iter().map(|e| e.binding).collect::<Vec<_>>(),
+        [0, 1, 2]
+    );
+    assert_eq!(
+        group_0[0].ty,
+        wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Depth,
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        }
+    );
+    assert_eq!(group_0[0].visibility, wgpu::ShaderStages::FRAGMENT);
+    assert_eq!(
+        group_0[1].ty,
+        wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison)
+    );
+    assert_eq!(
+        group_0[2].ty,
+        wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only: true },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        }
+    );
+    assert_eq!(group_0[2].visibility, wgpu::ShaderStages::VERTEX);
+
+    let group_1 = shader.bind_group_layout_entries(1).unwrap();
+    assert_eq!(
+        group_1[0].ty,
+        wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            view_dimension: wgpu::TextureViewDimension::Cube,
+            multisampled: false,
+        }
+    );
+    // Neither entry point uses these
+    assert_eq!(group_1[0].visibility, wgpu::ShaderStages::VERTEX_FRAGMENT);
+    assert_eq!(
+        group_1[1].ty,
+        wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only: false },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        }
+    );
+
+    assert!(shader.bind_group_layout_entries(2).unwrap().is_empty());
+}
+
+#[test]
+fn shared_layouts_are_visible_wherever_they_are_used() {
+    // The instanced shader only needs the camera in its vertex stage, the lit one
+    // needs its position for specular lighting too
+    let camera = merge_entries([
+        built_in(INSTANCED_SHADER)
+            .bind_group_layout_entries(1)
+            .unwrap(),
+        built_in(LIT_SHADER).bind_group_layout_entries(1).unwrap(),
+    ])
+    .unwrap();
+
+    assert_eq!(
+        camera,
+        [wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+            ty: uniform_buffer(),
+            count: None,
+        }]
+    );
+}
+
+#[test]
+fn shared_layouts_have_to_agree() {
+    let texture = built_in(INSTANCED_SHADER)
+        .bind_group_layout_entries(0)
+        .unwrap();
+    let camera = built_in(INSTANCED_SHADER)
+        .bind_group_layout_entries(1)
+        .unwrap();
+
+    let error = merge_entries([texture, camera]).unwrap_err().to_string();
+    assert!(error.contains("@binding(0)"), "{error}");
+}
+
+#[test]
+fn vertex_inputs_match_the_vertex_types() {
+    built_in(INSTANCED_SHADER)
+        .check_vertex_inputs("vs_main", &[TexturedVertex::desc(), InstanceRaw::desc()])
+        .unwrap();
+    built_in(LIT_SHADER)
+        .check_vertex_inputs("vs_main", &[ModelVertex::desc(), InstanceRaw::desc()])
+        .unwrap();
+    built_in(LIGHT_SHADER)
+        .check_vertex_inputs("vs_main", &[ModelVertex::desc()])
+        .unwrap();
+}
+
+#[test]
+fn missing_vertex_inputs_are_errors() {
+    // No instance buffer
+    let error = built_in(INSTANCED_SHADER)
+        .check_vertex_inputs("vs_main", &[TexturedVertex::desc()])
+        .unwrap_err()
+        .to_string();
+
+    assert!(
+        error.contains("vs_main reads @location(5) model_matrix_0: vec4<f32>"),
+        "{error}"
+    );
+}
+
+#[test]
+fn mismatched_vertex_inputs_are_errors() {
+    let shader = ShaderReflection::new(
+        "@vertex
+         fn vs_main(@location(0) position: vec3<f32>, @location(1) id: u32) -> @builtin(position) vec4<f32> {
+             return vec4<f32>(position, f32(id));
+         }",
+    )
+    .unwrap();
+    let attributes = wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32];
+    let buffer = wgpu::VertexBufferLayout {
+        array_stride: 16,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: &attributes,
+    };
+
+    let error = shader
+        .check_vertex_inputs("vs_main", &[buffer])
+        .unwrap_err()
+        .to_string();
+    assert_eq!(
+        error,
+        "vs_main reads @location(1) id as a u32, but the vertex attribute there is Float32"
+    );
+    assert!(shader.check_vertex_inputs("fs_main", &[]).is_err());
+}
+
+#[test]
+fn errors_have_a_line_and_column() {
+    let error = ShaderReflection::new(
+        "@vertex\nfn vs_main() -> @builtin(position) vec4<f32> {\n    return 1;\n}\n",
+    )
+    .err()
+    .unwrap()
+    .to_string();
+
+    assert!(error.starts_with("3:"), "{error}");
+}
+
+// Rgba32Float textures can't be filtered (without Features::FLOAT32_FILTERABLE),
+// which WGSL has no way to say, so their bindings are set by hand
+#[test]
+fn binding_types_can_be_set_by_hand() {
+    let source = "@group(0) @binding(0) var t_data: texture_2d<f32>;
+                  @group(0) @binding(1) var s_data: sampler;
+
+                  @fragment
+                  fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+                      return textureSample(t_data, s_data, position.xy);
+                  }";
+    let reflected = ShaderReflection::new(source)
+        .unwrap()
+        .bind_group_layout_entries(0)
+        .unwrap();
+    let mut shader = ShaderReflection::new(source).unwrap();
+
+    let unfilterable = wgpu::BindingType::Texture {
+        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+        view_dimension: wgpu::TextureViewDimension::D2,
+        multisampled: false,
+    };
+    let non_filtering = wgpu::BindingType::Sampler(wgpu::SamplerBindingType::NonFiltering);
+    shader.set_binding_type(0, 0, unfilterable).unwrap();
+    shader.set_binding_type(0, 1, non_filtering).unwrap();
+    let entries = shader.bind_group_layout_entries(0).unwrap();
+    assert_eq!(
+        entries.iter().map(|entry| entry.ty).collect::<Vec<_>>(),
+        [unfilterable, non_filtering]
+    );
+    assert_eq!(entries[0].visibility, wgpu::ShaderStages::FRAGMENT);
+
+    // It still has to be what the shader declares
+    for (binding, ty, expected) in [
+        (
+            1,
+            wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+            "can't be bound as",
+        ),
+        (0, uniform_buffer(), "can't be bound as"),
+        (2, non_filtering, "there's nothing at @group(0) @binding(2)"),
+    ] {
+        let error = shader
+            .set_binding_type(0, binding, ty)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(expected), "{error}");
+    }
+
+    // An Rgba32Float texture only fits the layout with the types set by hand
+    let Some((device, _)) = device() else { return };
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: 1,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba32Float,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    // Nearest everywhere, so it doesn't filter
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor::default());
+    let bind = |entries: &[wgpu::BindGroupLayoutEntry]| {
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries,
+        });
+        let _bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+        pollster::block_on(device.pop_error_scope())
+    };
+
+    assert!(bind(&entries).is_none());
+    assert!(bind(&reflected).is_some());
+}
@@ -0,0 +1,85 @@
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use wgpu_ex::types::shader_library::{INSTANCED_SHADER, LIT_SHADER, SHADOW_SHADER, ShaderLibrary};
+
+const RESOURCES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/types/resources");
+
+// A copy of the shaders that a test can edit
+fn shader_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    fs::create_dir_all(&dir).unwrap();
+    for entry in fs::read_dir(RESOURCES).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|e| e == "wgsl") {
+            fs::copy(&path, dir.join(path.file_name().unwrap())).unwrap();
+        }
+    }
+    dir
+}
+
+// Saves `source` with a modified time that's clearly later, however coarse the
+// file system's timestamps are
+fn save(path: PathBuf, source: &str) {
+    fs::write(&path, source).unwrap();
+    fs::File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(10))
+        .unwrap();
+}
+
+#[test]
+fn includes_are_looked_up_in_the_library() {
+    let dir = shader_dir("shader_library_includes");
+    save(dir.join("camera_uniform.wgsl"), "// replaced\n");
+    let library = ShaderLibrary::from_dir(&dir);
+
+    let shader = library.preprocessor().process(INSTANCED_SHADER).unwrap();
+    assert!(shader.source.contains("// replaced"));
+    assert!(
+        shader
+            .files()
+            .iter()
+            .any(|file| file == "textured_vertex.wgsl")
+    );
+}
+
+#[test]
+fn only_saved_files_count_as_changed() {
+    let dir = shader_dir("shader_library_changes");
+    let mut library = ShaderLibrary::from_dir(&dir);
+    library.poll_interval = Duration::ZERO;
+    assert!(library.is_watching());
+    assert!(library.poll_changes().is_empty());
+
+    let edited = format!(
+        "{}\n// edited\n",
+        ShaderLibrary::embedded_source(LIT_SHADER)
+    );
+    save(dir.join(LIT_SHADER), &edited);
+
+    assert_eq!(library.poll_changes(), vec![LIT_SHADER]);
+    assert_eq!(library.source(LIT_SHADER).unwrap(), edited);
+    // Nothing has changed since the last poll
+    assert!(library.poll_changes().is_empty());
+}
+
+#[test]
+fn polling_waits_for_the_interval() {
+    let dir = shader_dir("shader_library_interval");
+    let mut library = ShaderLibrary::from_dir(&dir);
+    library.poll_interval = Duration::from_secs(3600);
+    // The first poll always looks
+    assert!(library.poll_changes().is_empty());
+
+    save(
+        dir.join(SHADOW_SHADER),
+        ShaderLibrary::embedded_source(SHADOW_SHADER),
+    );
+    assert!(library.poll_changes().is_empty());
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use common::device;
+use wgpu_ex::types::texture::{ColorSpace, CubeTexture, Filtering, Texture, TextureOptions};
+
+fn checkerboard(width: u32, height: u32) -> image::DynamicImage {
+    image::DynamicImage::ImageRgba8(image::RgbaImage::from_fn(width, height, |x, y| {
+        if (x + y) % 2 == 0 {
+            image::Rgba([255; 4])
+        } else {
+            image::Rgba([0, 0, 0, 255])
+        }
+    }))
+}
+
+#[test]
+fn textures_have_one_level_by_default() {
+    let Some((device, queue)) = device() else {
+        return;
+    };
+    let texture = Texture::from_image(
+        &device,
+        &queue,
+        &checkerboard(8, 8),
+        None,
+        TextureOptions::default(),
+    )
+    .unwrap();
+
+    assert_eq!(texture.texture.mip_level_count(), 1);
+}
+
+#[test]
+fn mip_chain_goes_down_to_one_texel() {
+    let Some((device, queue)) = device() else {
+        return;
+    };
+
+    // Only the longest side matters, the other stops shrinking at 1
+    for (width, height, levels) in [(8, 8, 4), (256, 16, 9), (5, 3, 3), (1, 1, 1)] {
+        let texture = Texture::from_image(
+            &device,
+            &queue,
+            &checkerboard(width, height),
+            None,
+            TextureOptions::TRILINEAR,
+        )
+        .unwrap();
+        assert_eq!(
+            texture.texture.mip_level_count(),
+            levels,
+            "{width}x{height}"
+        );
+    }
+}
+
+#[test]
+fn mipmaps_work_for_linear_formats() {
+    let Some((device, queue)) = device() else {
+        return;
+    };
+    let options = TextureOptions {
+        color_space: ColorSpace::Linear,
+        ..TextureOptions::anisotropic(16)
+    };
+    let texture =
+        Texture::from_image(&device, &queue, &checkerboard(16, 16), None, options).unwrap();
+
+    assert_eq!(texture.texture.format(), wgpu::TextureFormat::Rgba8Unorm);
+    assert_eq!(texture.texture.mip_level_count(), 5);
+    // Validation errors from the blits would have panicked in here instead
+    device.poll(wgpu::Maintain::Wait);
+}
+
+#[test]
+fn extra_usage_is_added_to_the_defaults() {
+    let Some((device, queue)) = device() else {
+        return;
+    };
+    let options = TextureOptions {
+        color_space: ColorSpace::Linear,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::STORAGE_BINDING,
+        ..TextureOptions::DEFAULT
+    };
+    let texture = Texture::from_image(&device, &queue, &checkerboard(4, 4), None, options).unwrap();
+
+    let usage = texture.texture.usage();
+    assert!(usage.contains(wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST));
+    assert!(
+        usage.contains(
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::STORAGE_BINDING
+        )
+    );
+}
+
+#[test]
+fn srgb_storage_textures_are_refused() {
+    let Some((device, queue)) = device() else {
+        return;
+    };
+    let options = TextureOptions {
+        usage: wgpu::TextureUsages::STORAGE_BINDING,
+        ..TextureOptions::DEFAULT
+    };
+
+    assert!(Texture::from_image(&device, &queue, &checkerboard(4, 4), None, options).is_err());
+}
+
+#[test]
+fn nearest_filtering_needs_a_non_filtering_layout() {
+    let options = TextureOptions {
+        filtering: Filtering::Nearest,
+        address_mode_u: wgpu::AddressMode::Repeat,
+        ..TextureOptions::DEFAULT
+    };
+
+    let sampler = options.sampler_descriptor();
+    assert_eq!(sampler.mag_filter, wgpu::FilterMode::Nearest);
+    assert_eq!(sampler.address_mode_u, wgpu::AddressMode::Repeat);
+    assert_eq!(sampler.address_mode_v, wgpu::AddressMode::ClampToEdge);
+
+    let [texture, sampler] = options.layout_entries(wgpu::ShaderStages::FRAGMENT);
+    assert!(matches!(
+        texture.ty,
+        wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: false },
+            ..
+        }
+    ));
+    assert_eq!(
+        sampler.ty,
+        wgpu::BindingType::Sampler(wgpu::SamplerBindingType::NonFiltering)
+    );
+}
+
+#[test]
+fn cubemap_faces_have_to_be_square_and_match() {
+    let Some((device, queue)) = device() else {
+        return;
+    };
+    let mut faces: [_; 6] = std::array::from_fn(|_| checkerboard(8, 8));
+
+    let cube = CubeTexture::from_faces(&device, &queue, &faces, None, ColorSpace::Srgb).unwrap();
+    assert_eq!(cube.texture.depth_or_array_layers(), 6);
+
+    faces[5] = checkerboard(4, 4);
+    assert!(CubeTexture::from_faces(&device, &queue, &faces, None, ColorSpace::Srgb).is_err());
+    faces[5] = checkerboard(8, 4);
+    assert!(CubeTexture::from_faces(&device, &queue, &faces, None, ColorSpace::Srgb).is_err());
+    faces[5] = checkerboard(8, 0);
+    assert!(CubeTexture::from_faces(&device, &queue, &faces, None, ColorSpace::Srgb).is_err());
+
+    let empty: [_; 6] = std::array::from_fn(|_| checkerboard(0, 0));
+    assert!(CubeTexture::from_faces(&device, &queue, &empty, None, ColorSpace::Srgb).is_err());
+}
@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use wgpu_ex::types::timing::{FixedTimestep, FrameTimer};
+
+const MS: Duration = Duration::from_millis(1);
+
+#[test]
+fn frame_timer_measures_and_clamps_deltas() {
+    let start = web_time::Instant::now();
+    let mut timer = FrameTimer::new();
+
+    assert_eq!(timer.tick_at(start), Duration::ZERO);
+    assert_eq!(timer.tick_at(start + 16 * MS), 16 * MS);
+    assert_eq!(timer.delta(), 16 * MS);
+
+    // A long stall only counts as max_delta
+    assert_eq!(timer.tick_at(start + 10_016 * MS), timer.max_delta);
+    assert_eq!(timer.elapsed(), 16 * MS + timer.max_delta);
+}
+
+#[test]
+fn fixed_timestep_accumulates_partial_steps() {
+    let mut fixed = FixedTimestep::new(10 * MS);
+
+    assert_eq!(fixed.advance(4 * MS), 0);
+    assert!((fixed.alpha() - 0.4).abs() < 1e-6);
+
+    assert_eq!(fixed.advance(27 * MS), 3);
+    assert!((fixed.alpha() - 0.1).abs() < 1e-6);
+}
+
+#[test]
+fn fixed_timestep_drops_time_past_max_steps() {
+    let mut fixed = FixedTimestep::from_hz(100.0);
+    fixed.max_steps = 4;
+
+    assert_eq!(fixed.advance(95 * MS), 4);
+    assert_eq!(fixed.alpha(), 0.0);
+    assert_eq!(fixed.advance(10 * MS), 1);
+}
+
+#[test]
+#[should_panic(expected = "a fixed timestep needs a rate above 0 Hz, not 0")]
+fn fixed_timestep_needs_a_positive_rate() {
+    FixedTimestep::from_hz(0.0);
+}
+
+#[test]
+#[should_panic(expected = "a fixed timestep needs a rate above 0 Hz, not -60")]
+fn fixed_timestep_rejects_negative_rates() {
+    FixedTimestep::from_hz(-60.0);
+}
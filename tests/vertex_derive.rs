@@ -0,0 +1,101 @@
+use wgpu_ex::types::vertex_types::{
+    Vertex, instance::InstanceRaw, model_vertex::ModelVertex, pbr_vertex::PbrVertex,
+    textured_vertex::TexturedVertex,
+};
+
+// A bit of everything the derive handles
+#[repr(C)]
+#[derive(Copy, Clone, Vertex)]
+#[allow(dead_code)]
+struct Particle {
+    position: [f32; 3],
+    #[normalized]
+    color: [u8; 4],
+    cell: [u32; 2],
+    #[location(7)]
+    size: f32,
+    ids: [i16; 4],
+}
+
+fn attributes(layout: &wgpu::VertexBufferLayout) -> Vec<(u64, u32, wgpu::VertexFormat)> {
+    layout
+        .attributes
+        .iter()
+        .map(|a| (a.offset, a.shader_location, a.format))
+        .collect()
+}
+
+#[test]
+fn offsets_and_formats_come_from_the_fields() {
+    let layout = Particle::desc();
+
+    assert_eq!(layout.array_stride, 36);
+    assert_eq!(layout.step_mode, wgpu::VertexStepMode::Vertex);
+    assert_eq!(
+        attributes(&layout),
+        [
+            (0, 0, wgpu::VertexFormat::Float32x3),
+            (12, 1, wgpu::VertexFormat::Unorm8x4),
+            (16, 2, wgpu::VertexFormat::Uint32x2),
+            (24, 7, wgpu::VertexFormat::Float32),
+            (28, 8, wgpu::VertexFormat::Sint16x4),
+        ]
+    );
+}
+
+#[test]
+fn matrices_take_a_location_per_column() {
+    let layout = InstanceRaw::desc();
+
+    assert_eq!(layout.array_stride, 64);
+    assert_eq!(layout.step_mode, wgpu::VertexStepMode::Instance);
+    assert_eq!(
+        attributes(&layout),
+        [
+            (0, 5, wgpu::VertexFormat::Float32x4),
+            (16, 6, wgpu::VertexFormat::Float32x4),
+            (32, 7, wgpu::VertexFormat::Float32x4),
+            (48, 8, wgpu::VertexFormat::Float32x4),
+        ]
+    );
+}
+
+#[test]
+fn textured_vertex_keeps_its_layout() {
+    let layout = TexturedVertex::desc();
+
+    assert_eq!(layout.array_stride, 20);
+    assert_eq!(
+        attributes(&layout),
+        [
+            (0, 0, wgpu::VertexFormat::Float32x3),
+            (12, 1, wgpu::VertexFormat::Float32x2)
+        ]
+    );
+}
+
+#[test]
+fn model_and_pbr_vertices_keep_their_layouts() {
+    let layout = ModelVertex::desc();
+    assert_eq!(layout.array_stride, 32);
+    assert_eq!(
+        attributes(&layout),
+        [
+            (0, 0, wgpu::VertexFormat::Float32x3),
+            (12, 1, wgpu::VertexFormat::Float32x2),
+            (20, 2, wgpu::VertexFormat::Float32x3),
+        ]
+    );
+
+    let layout = PbrVertex::desc();
+    assert_eq!(layout.array_stride, 48);
+    assert_eq!(
+        attributes(&layout),
+        [
+            (0, 0, wgpu::VertexFormat::Float32x3),
+            (12, 1, wgpu::VertexFormat::Float32x2),
+            (20, 2, wgpu::VertexFormat::Float32x3),
+            (32, 3, wgpu::VertexFormat::Float32x4),
+        ]
+    );
+}
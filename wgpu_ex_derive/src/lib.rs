@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Error, Fields, Ident, Result, Type, parse_macro_input};
+
+// Implements wgpu_ex's Vertex trait, working the buffer layout out from the
+// struct's fields so nobody has to count bytes by hand:
+//
+//   #[repr(C)]
+//   #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+//   #[instance]                    // stepped per instance rather than per vertex
+//   struct Particle {
+//       position: [f32; 3],        // @location(0), Float32x3
+//       #[normalized]
+//       color: [u8; 4],            // @location(1), Unorm8x4
+//       #[location(5)]
+//       model: [[f32; 4]; 4],      // @location(5) to (8), one Float32x4 per column
+//       id: u32,                   // @location(9), Uint32
+//   }
+//
+// Fields take the location after the previous one unless they say otherwise.
+// The struct should be #[repr(C)] like anything else that's uploaded as bytes.
+#[proc_macro_derive(Vertex, attributes(instance, location, normalized))]
+pub fn derive_vertex(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "Vertex can't be derived for generic types",
+        ));
+    }
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            name,
+            "Vertex can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(
+            name,
+            "Vertex can only be derived for structs with named fields",
+        ));
+    };
+
+    let mut step_mode = quote!(Vertex);
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("instance"))
+    {
+        attr.meta.require_path_only()?;
+        step_mode = quote!(Instance);
+    }
+
+    let mut attributes = Vec::new();
+    // Which field has each location so far, to catch two fields sharing one
+    let mut used: HashMap<u32, &Ident> = HashMap::new();
+    let mut location = 0u32;
+    for field in &fields.named {
+        let ident = field.ident.as_ref().expect("named fields have names");
+        let mut normalized = false;
+        for attr in &field.attrs {
+            if attr.path().is_ident("location") {
+                location = attr.parse_args::<syn::LitInt>()?.base10_parse()?;
+            } else if attr.path().is_ident("normalized") {
+                attr.meta.require_path_only()?;
+                normalized = true;
+            }
+        }
+
+        // An array of vectors is a matrix, which takes a location per column
+        let (column_type, columns) = match &field.ty {
+            Type::Array(array) if matches!(*array.elem, Type::Array(_)) => {
+                (&*array.elem, array_len(array)?)
+            }
+            ty => (ty, 1),
+        };
+        let format = vertex_format(column_type, normalized)?;
+
+        for column in 0..columns as usize {
+            if let Some(other) = used.insert(location, ident) {
+                return Err(Error::new_spanned(
+                    ident,
+                    format!("@location({location}) is already used by `{other}`"),
+                ));
+            }
+            attributes.push(quote! {
+                ::wgpu::VertexAttribute {
+                    offset: (::core::mem::offset_of!(#name, #ident) + #column * ::core::mem::size_of::<#column_type>()) as ::wgpu::BufferAddress,
+                    shader_location: #location,
+                    format: ::wgpu::VertexFormat::#format,
+                }
+            });
+            location += 1;
+        }
+    }
+
+    Ok(quote! {
+        impl ::wgpu_ex::types::vertex_types::Vertex for #name {
+            fn desc() -> ::wgpu::VertexBufferLayout<'static> {
+                const ATTRIBUTES: &[::wgpu::VertexAttribute] = &[#(#attributes),*];
+                ::wgpu::VertexBufferLayout {
+                    array_stride: ::core::mem::size_of::<#name>() as ::wgpu::BufferAddress,
+                    step_mode: ::wgpu::VertexStepMode::#step_mode,
+                    attributes: ATTRIBUTES,
+                }
+            }
+        }
+    })
+}
+
+// The VertexFormat variant for a scalar or an array of up to 4 of them
+fn vertex_format(ty: &Type, normalized: bool) -> Result<Ident> {
+    let (scalar, len) = match ty {
+        Type::Array(array) => (&*array.elem, array_len(array)?),
+        ty => (ty, 1),
+    };
+    let scalar_name = match scalar {
+        Type::Path(path) => path.path.get_ident().map(Ident::to_string),
+        _ => None,
+    };
+    let unsupported = || {
+        Error::new_spanned(
+            ty,
+            "vertex attributes have to be a scalar or an array of up to 4 scalars",
+        )
+    };
+
+    // The name of the format without the count, and which counts it comes in
+    let (base, lens): (&str, &[u64]) =
+        match (scalar_name.as_deref().ok_or_else(unsupported)?, normalized) {
+            ("f32", false) => ("Float32", &[1, 2, 3, 4]),
+            ("f64", false) => ("Float64", &[1, 2, 3, 4]),
+            ("u32", false) => ("Uint32", &[1, 2, 3, 4]),
+            ("i32", false) => ("Sint32", &[1, 2, 3, 4]),
+            ("u16", false) => ("Uint16", &[1, 2, 4]),
+            ("u16", true) => ("Unorm16", &[1, 2, 4]),
+            ("i16", false) => ("Sint16", &[1, 2, 4]),
+            ("i16", true) => ("Snorm16", &[1, 2, 4]),
+            ("u8", false) => ("Uint8", &[1, 2, 4]),
+            ("u8", true) => ("Unorm8", &[1, 2, 4]),
+            ("i8", false) => ("Sint8", &[1, 2, 4]),
+            ("i8", true) => ("Snorm8", &[1, 2, 4]),
+            (_, true) => {
+                return Err(Error::new_spanned(
+                    ty,
+                    "only 8 and 16 bit integers can be #[normalized]",
+                ));
+            }
+            _ => return Err(unsupported()),
+        };
+    if !lens.contains(&len) {
+        return Err(Error::new_spanned(
+            ty,
+            format!(
+                "there's no vertex format for {len} of {}",
+                scalar_name.unwrap()
+            ),
+        ));
+    }
+
+    Ok(match len {
+        1 => format_ident!("{base}"),
+        len => format_ident!("{base}x{len}"),
+    })
+}
+
+fn array_len(array: &syn::TypeArray) -> Result<u64> {
+    match &array.len {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(len),
+            ..
+        }) => len.base10_parse(),
+        len => Err(Error::new_spanned(
+            len,
+            "array lengths have to be written as a number",
+        )),
+    }
+}